@@ -0,0 +1,167 @@
+use crate::process::Cancellation;
+use chrono::Utc;
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// 设备是否处于可用状态（`adb get-state` 输出 device）
+pub async fn device_ready(target: &str) -> bool {
+    Command::new("adb")
+        .arg("-s")
+        .arg(target)
+        .arg("get-state")
+        .output()
+        .await
+        .is_ok_and(|output| {
+            output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "device"
+        })
+}
+
+/// 重启adb server，清理上次运行留下的连接
+pub async fn restart_server() -> io::Result<()> {
+    Command::new("adb").arg("kill-server").output().await?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let output = Command::new("adb").arg("start-server").output().await?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// 连接模拟器并等待设备就绪，失败时每隔 `interval` 重试，最多尝试 `attempts` 次，返回连接上的adb地址；
+/// 运行被取消时立即返回None
+///
+/// 每次尝试前通过 `target` 取adb地址，取不到地址时同样等待重试
+pub async fn connect_with_retry<F, Fut>(
+    mut target: F,
+    attempts: u32,
+    interval: Duration,
+    cancellation: &Cancellation,
+) -> Option<String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Option<String>>,
+{
+    for attempt in 1..=attempts.max(1) {
+        if cancellation.is_cancelled() {
+            return None;
+        }
+        let pending = match target().await {
+            Some(target) => {
+                match Command::new("adb")
+                    .arg("connect")
+                    .arg(&target)
+                    .output()
+                    .await
+                {
+                    Ok(output) => log::info!("{:?}", String::from_utf8_lossy(&output.stdout)),
+                    Err(e) => {
+                        log::error!("adb启动失败: {}", e);
+                        return None;
+                    }
+                }
+                if device_ready(&target).await {
+                    return Some(target);
+                }
+                format!("设备{}尚未就绪", target)
+            }
+            None => "尚未获取到adb地址".to_string(),
+        };
+        if attempt < attempts {
+            log::warn!(
+                "{}({}/{})，{}秒后重试",
+                pending,
+                attempt,
+                attempts,
+                interval.as_secs()
+            );
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = cancellation.cancelled() => return None,
+            }
+        }
+    }
+    None
+}
+
+/// 游戏进程是否在运行（`adb shell pidof <包名>` 有输出）
+pub async fn game_running(target: &str, package: &str) -> bool {
+    Command::new("adb")
+        .arg("-s")
+        .arg(target)
+        .args(["shell", "pidof", package])
+        .output()
+        .await
+        .is_ok_and(|output| {
+            output.status.success() && !String::from_utf8_lossy(&output.stdout).trim().is_empty()
+        })
+}
+
+/// 设备当前的Unix时间戳
+pub async fn device_time(target: &str) -> Option<i64> {
+    let output = Command::new("adb")
+        .arg("-s")
+        .arg(target)
+        .args(["shell", "date", "+%s"])
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// 把设备时间设为宿主机当前时间，需要adb有root权限（redroid默认即为root）
+pub async fn sync_time(target: &str) -> bool {
+    // toybox的date设置时间的格式为 MMDDhhmmYYYY.ss
+    let now = Utc::now().format("%m%d%H%M%Y.%S").to_string();
+    Command::new("adb")
+        .arg("-s")
+        .arg(target)
+        .args(["shell", "date", "-u", &now])
+        .output()
+        .await
+        .is_ok_and(|output| output.status.success())
+}
+
+/// 通过monkey发送启动器intent拉起游戏
+pub async fn launch_game(target: &str, package: &str) -> bool {
+    Command::new("adb")
+        .arg("-s")
+        .arg(target)
+        .args(["shell", "monkey", "-p", package])
+        .args(["-c", "android.intent.category.LAUNCHER", "1"])
+        .output()
+        .await
+        .is_ok_and(|output| output.status.success())
+}
+
+/// 等待游戏进程就绪，`auto_launch` 时游戏未运行会先尝试拉起游戏
+pub async fn wait_for_game(
+    target: &str,
+    package: &str,
+    auto_launch: bool,
+    timeout: Duration,
+) -> bool {
+    if game_running(target, package).await {
+        return true;
+    }
+    if auto_launch {
+        log::info!("游戏未运行，正在启动{}", package);
+        if !launch_game(target, package).await {
+            log::warn!("启动游戏{}失败", package);
+        }
+    }
+    let deadline = tokio::time::Instant::now() + timeout;
+    while tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        if game_running(target, package).await {
+            return true;
+        }
+    }
+    false
+}
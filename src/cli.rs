@@ -0,0 +1,68 @@
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use std::io;
+
+/// 一键启动容器、连接adb并运行MAA日常任务
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    /// 强制运行（手动触发时使用）：忽略每日最大运行次数、排除日期（SKIP_DATES/SKIP_DATES_ICS）与用户空闲检测
+    #[arg(long)]
+    pub force: bool,
+
+    /// 以NDJSON格式把各阶段的开始/结束事件输出到stdout（每个事件带有格式版本version），日志改为输出到stderr
+    #[arg(long)]
+    pub progress_json: bool,
+
+    /// 同时把日志写入journald（SYSLOG_IDENTIFIER=easy_maa，并带有本次运行的RUN_ID字段）
+    #[arg(long)]
+    pub journald: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// 生成shell补全脚本并输出到标准输出
+    Completions {
+        /// 目标shell
+        shell: Shell,
+    },
+    /// 生成man page并输出到标准输出
+    Man,
+    /// 输出easy_maa使用的数据目录
+    Paths,
+    /// 统计最近30天最常见的失败
+    Failures,
+    /// 列出每次运行的工作目录，指定RUN_ID时列出该次运行的产物
+    Artifacts {
+        /// 运行的RUN_ID
+        run_id: Option<String>,
+    },
+    /// 检查运行环境（GPU透传、模拟器时间等）并给出配置建议
+    Doctor,
+    /// 把模拟器的时间同步为宿主机时间
+    SyncTime,
+    /// 清理podman的悬空镜像与已停止的容器，默认只预览
+    Prune {
+        /// 同时清理已停止的容器（CONTAINER_NAME指定的容器除外）
+        #[arg(long)]
+        containers: bool,
+        /// 确认执行清理
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+/// 输出补全脚本，例如 `easy_maa completions bash > /usr/share/bash-completion/completions/easy_maa`
+pub fn print_completions(shell: Shell) {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, name, &mut io::stdout());
+}
+
+/// 输出man page，例如 `easy_maa man > /usr/share/man/man1/easy_maa.1`
+pub fn print_man() -> io::Result<()> {
+    clap_mangen::Man::new(Cli::command()).render(&mut io::stdout())
+}
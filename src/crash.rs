@@ -0,0 +1,126 @@
+use chrono::Local;
+use regex::Regex;
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// 崩溃报告里附带的最近日志行数
+const RECENT_LOG_LINES: usize = 100;
+// 记录尚未提示过的崩溃报告路径，下次启动时提示后删除
+const PENDING_FILE: &str = "PENDING";
+
+static RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// 日志输出的包装，写入原输出的同时保留最近的日志，供崩溃报告使用
+pub struct LogTee<W> {
+    inner: W,
+}
+
+impl<W: Write> LogTee<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+}
+
+impl<W: Write> Write for LogTee<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if let Ok(mut logs) = RECENT_LOGS.lock() {
+            for line in String::from_utf8_lossy(&buf[..written]).lines() {
+                if logs.len() == RECENT_LOG_LINES {
+                    logs.pop_front();
+                }
+                logs.push_back(line.to_string());
+            }
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 注册panic hook，panic时在 `dir` 下生成崩溃报告，原有的panic输出保持不变
+pub fn install_panic_hook(dir: PathBuf) {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        let report = format!(
+            "easy_maa 崩溃报告\n\n时间: {}\n版本: {}\n系统: {}\n内核: {}\n架构: {}\n\n{}\n\n调用栈:\n{}\n\n最近日志:\n{}\n",
+            Local::now().to_rfc3339(),
+            env!("CARGO_PKG_VERSION"),
+            os_name(),
+            fs::read_to_string("/proc/sys/kernel/osrelease")
+                .unwrap_or_default()
+                .trim(),
+            env::consts::ARCH,
+            info,
+            Backtrace::force_capture(),
+            recent_logs(),
+        );
+        match write_report(&dir, &report) {
+            Ok(path) => eprintln!("崩溃报告已保存到: {}", path.display()),
+            Err(e) => eprintln!("崩溃报告保存失败: {}", e),
+        }
+    }));
+}
+
+/// 启动时提示上次运行留下的崩溃报告
+pub fn report_pending(dir: &Path) {
+    let pending = dir.join(PENDING_FILE);
+    let Ok(content) = fs::read_to_string(&pending) else {
+        return;
+    };
+    for path in content.lines().filter(|line| !line.is_empty()) {
+        log::warn!(
+            "上次运行发生了崩溃，崩溃报告: {}[提示:提issue时请附上该文件]",
+            path
+        );
+    }
+    let _ = fs::remove_file(pending);
+}
+
+fn write_report(dir: &Path, report: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "crash-{}.txt",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::write(&path, report)?;
+    let mut pending = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(PENDING_FILE))?;
+    writeln!(pending, "{}", path.display())?;
+    Ok(path)
+}
+
+fn recent_logs() -> String {
+    let Ok(logs) = RECENT_LOGS.lock() else {
+        return String::new();
+    };
+    // 去掉终端颜色控制符
+    let ansi = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
+    logs.iter()
+        .map(|line| ansi.replace_all(line, "").into_owned())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn os_name() -> String {
+    fs::read_to_string("/etc/os-release")
+        .ok()
+        .and_then(|content| {
+            content.lines().find_map(|line| {
+                line.strip_prefix("PRETTY_NAME=")
+                    .map(|name| name.trim_matches('"').to_string())
+            })
+        })
+        .unwrap_or_else(|| env::consts::OS.to_string())
+}
@@ -0,0 +1,82 @@
+use crate::paths;
+use std::fs;
+use std::io;
+use std::os::unix::fs::{PermissionsExt, chown};
+use std::path::{Path, PathBuf};
+
+/// easy_maa自己的数据目录，统一在这里管理，各模块不再自行拼接路径
+///
+/// 目录位于安装MAA的用户的家目录下，使用sudo运行时也不会写到 /root
+pub struct DataDirs {
+    user_name: String,
+    state: PathBuf,
+}
+
+impl DataDirs {
+    pub fn new(user_name: &str, user_home: &Path) -> Self {
+        Self {
+            user_name: user_name.to_string(),
+            state: user_home.join(".local/state/easy_maa"),
+        }
+    }
+
+    /// 运行状态：每日运行次数、运行锁等
+    pub fn state(&self) -> &Path {
+        &self.state
+    }
+
+    /// 崩溃报告
+    pub fn crash_reports(&self) -> PathBuf {
+        self.state.join("crash_reports")
+    }
+
+    /// 每次运行的工作目录
+    pub fn runs(&self) -> PathBuf {
+        self.state.join("runs")
+    }
+
+    /// 全部目录，用于创建目录与 `easy_maa paths` 输出
+    pub fn all(&self) -> Vec<(&'static str, PathBuf)> {
+        vec![
+            ("state", self.state.clone()),
+            ("crash_reports", self.crash_reports()),
+            ("runs", self.runs()),
+        ]
+    }
+
+    /// 创建缺失的目录并校验权限：目录仅所属用户可访问，以root运行时把目录交还给原始用户
+    pub fn ensure(&self) -> io::Result<()> {
+        let owner = paths::root_owner(&self.user_name);
+        for (_, dir) in self.all() {
+            if !dir.exists() {
+                create_dirs(&dir, owner)?;
+                fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
+            }
+            if let Some((uid, gid)) = owner {
+                chown(&dir, Some(uid), Some(gid))?;
+            }
+            let meta = fs::metadata(&dir)?;
+            if meta.permissions().readonly() || !meta.is_dir() {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("数据目录不可写: {}", dir.display()),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 逐级创建目录，`owner` 不为空时把新建的每一级都交给原始用户
+///
+/// 以root运行时 ~/.local、~/.local/state 等父目录也可能是新建的，不交还的话用户自己的 maa-cli 无法写入
+fn create_dirs(dir: &Path, owner: Option<(u32, u32)>) -> io::Result<()> {
+    let missing: Vec<&Path> = dir.ancestors().take_while(|path| !path.exists()).collect();
+    for path in missing.into_iter().rev() {
+        fs::create_dir(path)?;
+        if let Some((uid, gid)) = owner {
+            chown(path, Some(uid), Some(gid))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use regex::Regex;
+use std::fmt;
+
+/// 错误的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    SeLinux,
+    AppArmor,
+    /// 容器端口已被占用
+    PortConflict,
+    /// MAA连接模拟器失败
+    MaaConnection,
+    /// MAA资源加载失败或版本过旧
+    MaaResource,
+    /// MAA截图失败
+    MaaScreencap,
+    /// 游戏停止运行或未能启动
+    MaaGame,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::SeLinux => write!(f, "SELinux"),
+            ErrorKind::AppArmor => write!(f, "AppArmor"),
+            ErrorKind::PortConflict => write!(f, "端口"),
+            ErrorKind::MaaConnection => write!(f, "MAA连接"),
+            ErrorKind::MaaResource => write!(f, "MAA资源"),
+            ErrorKind::MaaScreencap => write!(f, "MAA截图"),
+            ErrorKind::MaaGame => write!(f, "游戏"),
+        }
+    }
+}
+
+/// 从命令错误输出里分析出的结构化错误，带修复建议
+#[derive(Debug, Clone)]
+pub struct Diagnosis {
+    pub kind: ErrorKind,
+    pub summary: &'static str,
+    pub suggestions: &'static [&'static str],
+    /// 相关文档
+    pub doc: Option<&'static str>,
+}
+
+impl fmt::Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.kind, self.summary)?;
+        for suggestion in self.suggestions {
+            write!(f, "\n  - {}", suggestion)?;
+        }
+        if let Some(doc) = self.doc {
+            write!(f, "\n  参考文档: {}", doc)?;
+        }
+        Ok(())
+    }
+}
+
+struct Rule {
+    pattern: &'static str,
+    diagnosis: Diagnosis,
+}
+
+// 常见错误的知识库：按报错特征匹配，给出修复建议与参考文档
+//
+// podman的报错往往只有一句 permission denied，这里按常见的报错特征识别安全模块引起的失败
+const CONTAINER_RULES: &[Rule] = &[
+    Rule {
+        pattern: r"(?i)avc:\s+denied|selinux|container_file_t|relabel",
+        diagnosis: Diagnosis {
+            kind: ErrorKind::SeLinux,
+            summary: "SELinux拒绝了容器访问挂载的文件",
+            suggestions: &[
+                "为挂载卷加上 :z（多容器共享）或 :Z（单容器独占）标签，例如 -v /data:/data:Z",
+                "如需容器管理cgroup，执行 sudo setsebool -P container_manage_cgroup on",
+                "使用 sudo ausearch -m avc -ts recent 查看具体的拒绝记录",
+            ],
+            doc: Some("https://docs.podman.io/en/latest/markdown/podman-run.1.html"),
+        },
+    },
+    Rule {
+        pattern: r"(?i)apparmor",
+        diagnosis: Diagnosis {
+            kind: ErrorKind::AppArmor,
+            summary: "AppArmor策略阻止了容器启动",
+            suggestions: &[
+                "使用 sudo dmesg | grep -i apparmor 查看被拒绝的操作",
+                "确认容器的AppArmor配置，必要时创建容器时加上 --security-opt apparmor=unconfined",
+            ],
+            doc: Some("https://docs.podman.io/en/latest/markdown/podman-run.1.html"),
+        },
+    },
+    Rule {
+        pattern: r"(?i)(mount|mounting|bind).*permission denied|permission denied.*(mount|volume)",
+        diagnosis: Diagnosis {
+            kind: ErrorKind::SeLinux,
+            summary: "挂载卷时权限被拒绝，通常由SELinux标签引起",
+            suggestions: &[
+                "为挂载卷加上 :z 或 :Z 标签后重新创建容器",
+                "执行 getenforce 确认SELinux状态，临时执行 sudo setenforce 0 可验证是否为SELinux导致",
+            ],
+            doc: Some("https://docs.podman.io/en/latest/markdown/podman-run.1.html"),
+        },
+    },
+    Rule {
+        pattern: r"(?i)address already in use|port is already allocated|bind: .*in use",
+        diagnosis: Diagnosis {
+            kind: ErrorKind::PortConflict,
+            summary: "容器映射的端口已被其它程序占用",
+            suggestions: &[
+                "执行 sudo ss -ltnp 查看占用端口的进程",
+                "停止占用端口的程序，或重新创建容器时改用其它宿主机端口并同步修改 ADB_TARGET",
+            ],
+            doc: Some("https://docs.podman.io/en/latest/markdown/podman-port.1.html"),
+        },
+    },
+];
+
+// MAA日志（asst.log）中常见的失败特征
+const MAA_RULES: &[Rule] = &[
+    Rule {
+        pattern: r"(?i)connect(ion)? ?failed|unable to connect|device offline|adb.*(error|failed|offline)",
+        diagnosis: Diagnosis {
+            kind: ErrorKind::MaaConnection,
+            summary: "MAA无法连接模拟器",
+            suggestions: &[
+                "确认 ADB_TARGET 与MAA配置中的连接地址一致",
+                "执行 adb devices 检查设备是否为device状态",
+                "设备显示offline时执行 adb disconnect 后重新运行，仍不行则重启容器",
+            ],
+            doc: Some("https://maa.plus/docs/zh-cn/manual/connection.html"),
+        },
+    },
+    Rule {
+        pattern: r"(?i)(load|loading) resource.*fail|resource.*(not found|invalid|outdated|expired)|version.*too (old|low)",
+        diagnosis: Diagnosis {
+            kind: ErrorKind::MaaResource,
+            summary: "MAA资源加载失败",
+            suggestions: &[
+                "执行 maa update 与 maa hot-update 更新MAA本体与资源",
+                "检查 ~/.local/share/maa 下的资源目录是否完整",
+            ],
+            doc: Some("https://github.com/MaaAssistantArknights/maa-cli"),
+        },
+    },
+    Rule {
+        pattern: r"(?i)screencap.*fail|screenshot.*fail",
+        diagnosis: Diagnosis {
+            kind: ErrorKind::MaaScreencap,
+            summary: "MAA截图失败",
+            suggestions: &[
+                "检查模拟器分辨率是否为16:9（推荐1280x720）",
+                "尝试在MAA配置中更换截图方式",
+            ],
+            doc: Some("https://maa.plus/docs/zh-cn/manual/connection.html"),
+        },
+    },
+    Rule {
+        pattern: r"(?i)(game|app|client).*(crash|stopped|not running)|start ?up.*fail",
+        diagnosis: Diagnosis {
+            kind: ErrorKind::MaaGame,
+            summary: "游戏未能正常运行",
+            suggestions: &["检查游戏是否需要更新，或在模拟器中手动启动一次游戏"],
+            doc: Some("https://github.com/remote-android/redroid-doc"),
+        },
+    },
+];
+
+fn find(rules: &[Rule], text: &str) -> Option<Diagnosis> {
+    rules
+        .iter()
+        .find(|rule| Regex::new(rule.pattern).unwrap().is_match(text))
+        .map(|rule| rule.diagnosis.clone())
+}
+
+/// 分析容器相关命令的错误输出，识别不出已知模式时返回None
+pub fn diagnose_container_error(stderr: &str) -> Option<Diagnosis> {
+    find(CONTAINER_RULES, stderr)
+}
+
+/// 分析MAA日志中的错误行，识别不出已知模式时返回None
+pub fn diagnose_maa_log(log: &str) -> Option<Diagnosis> {
+    find(MAA_RULES, log)
+}
@@ -0,0 +1,130 @@
+use crate::adb;
+use chrono::Utc;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+// 模拟器与宿主机允许的最大时间偏差（秒）
+const MAX_TIME_DRIFT: i64 = 30;
+
+/// `easy_maa doctor`：检查运行环境并给出配置建议，返回是否全部通过
+///
+/// GPU检查只针对redroid容器，未配置 CONTAINER_NAME 时跳过；时间检查需要模拟器正在运行
+pub async fn run(container_name: Option<&str>, adb_target: Option<&str>) -> bool {
+    let gpu = match container_name {
+        Some(name) => check_gpu(name),
+        None => {
+            log::info!("未配置CONTAINER_NAME，跳过容器GPU检查");
+            true
+        }
+    };
+    let time = match adb_target {
+        Some(target) => check_time(target).await,
+        None => true,
+    };
+    gpu && time
+}
+
+/// 比较模拟器与宿主机的时间，时间偏差过大会导致游戏服务器拒绝连接
+async fn check_time(target: &str) -> bool {
+    if !adb::device_ready(target).await {
+        log::info!("设备{}未连接，跳过时间检查", target);
+        return true;
+    }
+    let Some(device) = adb::device_time(target).await else {
+        log::warn!("无法读取设备{}的时间", target);
+        return false;
+    };
+    let drift = device - Utc::now().timestamp();
+    if drift.abs() > MAX_TIME_DRIFT {
+        log::warn!("模拟器时间与宿主机相差{}秒，游戏可能无法连接服务器", drift);
+        log::warn!("建议: 执行 easy_maa sync-time 同步模拟器时间");
+        return false;
+    }
+    log::info!("模拟器时间与宿主机相差{}秒", drift);
+    true
+}
+
+/// 宿主机上 /dev/dri 设备对应的驱动名称，如 i915、amdgpu、nvidia
+fn host_gpu_drivers() -> Vec<String> {
+    let Ok(entries) = fs::read_dir("/sys/class/drm") else {
+        return Vec::new();
+    };
+    let mut drivers: Vec<String> = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("card") && !name.contains('-')
+        })
+        .filter_map(|entry| {
+            let driver = fs::read_link(entry.path().join("device/driver")).ok()?;
+            Some(driver.file_name()?.to_string_lossy().into_owned())
+        })
+        .collect();
+    drivers.sort();
+    drivers.dedup();
+    drivers
+}
+
+/// 容器创建时透传的宿主机设备
+fn container_devices(container_name: &str) -> Option<Vec<String>> {
+    let output = Command::new("podman")
+        .arg("inspect")
+        .arg("--format")
+        .arg("{{range .HostConfig.Devices}}{{.PathOnHost}} {{end}}")
+        .arg(container_name)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .map(String::from)
+            .collect(),
+    )
+}
+
+fn check_gpu(container_name: &str) -> bool {
+    let drivers = host_gpu_drivers();
+    if !Path::new("/dev/dri").exists() || drivers.is_empty() {
+        log::warn!("宿主机没有可用的 /dev/dri 设备，redroid只能使用软件渲染，画面会很慢");
+        log::warn!("建议: 创建容器时加上 androidboot.redroid_gpu_mode=guest 参数");
+        return false;
+    }
+    log::info!("宿主机GPU驱动: {}", drivers.join(", "));
+
+    let Some(devices) = container_devices(container_name) else {
+        log::error!(
+            "无法获取容器{}的设备信息，请检查容器是否存在",
+            container_name
+        );
+        return false;
+    };
+    let has_dri = devices.iter().any(|device| device.starts_with("/dev/dri"));
+    if has_dri {
+        log::info!("容器已透传GPU设备: {}", devices.join(", "));
+    } else {
+        log::warn!("容器{}没有透传 /dev/dri，渲染会非常慢", container_name);
+    }
+
+    // redroid的GPU加速依赖mesa，nvidia私有驱动不支持host模式
+    let mesa = drivers
+        .iter()
+        .any(|driver| matches!(driver.as_str(), "i915" | "xe" | "amdgpu" | "radeon"));
+    if mesa {
+        if !has_dri {
+            log::warn!(
+                "建议: 重新创建容器时加上 --device /dev/dri 与 androidboot.redroid_gpu_mode=host 参数"
+            );
+        }
+    } else {
+        log::warn!(
+            "驱动{}不支持redroid的host渲染模式，建议使用 androidboot.redroid_gpu_mode=guest",
+            drivers.join(", ")
+        );
+    }
+    has_dri && mesa
+}
@@ -0,0 +1,160 @@
+use super::{Emulator, EmulatorError, EmulatorFuture};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tokio::process::Command;
+
+/// 可用的compose工具
+#[derive(Debug, Clone, Copy)]
+pub enum ComposeTool {
+    PodmanCompose,
+    DockerCompose,
+}
+
+impl fmt::Display for ComposeTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComposeTool::PodmanCompose => write!(f, "podman-compose"),
+            ComposeTool::DockerCompose => write!(f, "docker compose"),
+        }
+    }
+}
+
+impl ComposeTool {
+    /// 依次探测 podman-compose 与 docker compose，返回第一个可用的
+    pub async fn detect() -> Option<Self> {
+        for tool in [ComposeTool::PodmanCompose, ComposeTool::DockerCompose] {
+            if tool
+                .command()
+                .arg("version")
+                .output()
+                .await
+                .is_ok_and(|output| output.status.success())
+            {
+                return Some(tool);
+            }
+        }
+        None
+    }
+
+    fn command(&self) -> Command {
+        match self {
+            ComposeTool::PodmanCompose => Command::new("podman-compose"),
+            ComposeTool::DockerCompose => {
+                let mut cmd = Command::new("docker");
+                cmd.arg("compose");
+                cmd
+            }
+        }
+    }
+}
+
+/// 用compose文件描述的整套模拟器环境
+pub struct ComposeEnv {
+    /// 在check时探测到的compose工具，找不到时为None
+    tool: OnceLock<Option<ComposeTool>>,
+    file: PathBuf,
+}
+
+/// 一次compose命令的执行结果，stdout与stderr合并在一起方便推送
+struct ComposeOutput {
+    success: bool,
+    log: String,
+}
+
+impl ComposeEnv {
+    pub fn new(file: PathBuf) -> Self {
+        Self {
+            tool: OnceLock::new(),
+            file,
+        }
+    }
+
+    /// 执行compose命令并逐行打印输出，失败时把输出带在错误里推送出去
+    async fn run_logged(&self, args: &[&str], failed: &str) -> Result<(), EmulatorError> {
+        let output = self.run(args).await;
+        for line in output.log.lines() {
+            log::info!("[compose] {}", line);
+        }
+        if output.success {
+            Ok(())
+        } else {
+            let mut err = EmulatorError::new(failed, output.log);
+            err.notify = true;
+            Err(err)
+        }
+    }
+
+    async fn run(&self, args: &[&str]) -> ComposeOutput {
+        let Some(tool) = self.tool.get().copied().flatten() else {
+            return ComposeOutput {
+                success: false,
+                log: "未找到可用的compose工具".to_string(),
+            };
+        };
+        let mut cmd = tool.command();
+        cmd.arg("-f").arg(&self.file).args(args);
+        // compose文件里的相对路径以compose文件所在目录为准
+        if let Some(dir) = self.file.parent().filter(|dir| dir != &Path::new("")) {
+            cmd.current_dir(dir);
+        }
+        match cmd.output().await {
+            Ok(output) => {
+                let mut log = String::from_utf8_lossy(&output.stdout).into_owned();
+                log.push_str(&String::from_utf8_lossy(&output.stderr));
+                ComposeOutput {
+                    success: output.status.success(),
+                    log: log.trim().to_string(),
+                }
+            }
+            Err(e) => ComposeOutput {
+                success: false,
+                log: format!("{} 启动失败: {}", tool, e),
+            },
+        }
+    }
+}
+
+impl Emulator for ComposeEnv {
+    fn label(&self) -> String {
+        format!("compose环境 {}", self.file.display())
+    }
+
+    // compose工具在check时探测，找不到时由check报错
+    fn programs(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    fn check(&self) -> EmulatorFuture<'_> {
+        Box::pin(async move {
+            let detected = ComposeTool::detect().await;
+            match *self.tool.get_or_init(|| detected) {
+                Some(tool) => {
+                    log::info!("使用{}管理环境: {:?}", tool, self.file);
+                    Ok(())
+                }
+                None => {
+                    log::error!("已配置COMPOSE_FILE，但未找到可用的podman-compose或docker compose");
+                    Err(EmulatorError::new("未找到可用的compose工具", ""))
+                }
+            }
+        })
+    }
+
+    fn start(&self) -> EmulatorFuture<'_> {
+        Box::pin(async move {
+            self.run_logged(&["up", "-d"], "compose环境启动失败")
+                .await?;
+            log::info!("compose环境已启动");
+            Ok(())
+        })
+    }
+
+    fn stop(&self) -> EmulatorFuture<'_> {
+        Box::pin(async move {
+            self.run_logged(&["down"], "compose环境关闭失败").await?;
+            log::info!("compose环境已关闭");
+            Ok(())
+        })
+    }
+}
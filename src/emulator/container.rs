@@ -0,0 +1,109 @@
+use super::{Emulator, EmulatorError, EmulatorFuture};
+use tokio::process::Command;
+
+/// 单个podman容器（redroid）
+pub struct Container {
+    name: String,
+}
+
+impl Container {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+
+    /// 容器当前状态（running、exited等），容器不存在或podman调用失败时返回None
+    async fn status(&self) -> Option<String> {
+        let output = Command::new("podman")
+            .arg("inspect")
+            .arg("--format")
+            .arg("{{.State.Status}}")
+            .arg(&self.name)
+            .output()
+            .await
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    async fn podman(&self, action: &str) -> Result<(), EmulatorError> {
+        let output = Command::new("podman")
+            .arg(action)
+            .arg(&self.name)
+            .output()
+            .await
+            .map_err(|e| EmulatorError::new("podman启动失败", e.to_string()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(EmulatorError::new(
+                format!(
+                    "容器{}失败",
+                    if action == "start" {
+                        "启动"
+                    } else {
+                        "关闭"
+                    }
+                ),
+                stderr.trim(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Emulator for Container {
+    fn label(&self) -> String {
+        format!("容器{}", self.name)
+    }
+
+    fn programs(&self) -> Vec<&'static str> {
+        vec!["podman"]
+    }
+
+    fn check(&self) -> EmulatorFuture<'_> {
+        Box::pin(async move {
+            let output = Command::new("podman")
+                .arg("ps")
+                .arg("-a")
+                .output()
+                .await
+                .map_err(|e| EmulatorError::new("podman启动失败", e.to_string()))?;
+            let podman = String::from_utf8_lossy(&output.stdout);
+            if podman.contains(self.name.as_str()) {
+                log::info!("已找到运行Arknights的容器");
+                Ok(())
+            } else {
+                log::error!(
+                    "请检查容器是否存在以及.env配置是否正确[提示:你是否使用sudo权限运行该工具?]"
+                );
+                Err(EmulatorError::new("找不到运行Arknights的容器", ""))
+            }
+        })
+    }
+
+    fn start(&self) -> EmulatorFuture<'_> {
+        Box::pin(async move {
+            if self.status().await.as_deref() == Some("running") {
+                log::info!("容器已处于运行状态，无需启动");
+                return Ok(());
+            }
+            self.podman("start").await?;
+            log::info!("容器已启动");
+            Ok(())
+        })
+    }
+
+    fn stop(&self) -> EmulatorFuture<'_> {
+        Box::pin(async move {
+            // 容器已经停止时不再重复执行stop
+            if self.status().await.as_deref() != Some("running") {
+                log::info!("容器已处于停止状态，无需关闭");
+                return Ok(());
+            }
+            self.podman("stop").await?;
+            log::info!("已关闭podman容器");
+            Ok(())
+        })
+    }
+}
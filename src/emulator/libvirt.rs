@@ -0,0 +1,138 @@
+use super::{Emulator, EmulatorError, EmulatorFuture};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::process::Command;
+
+// 等待虚拟机正常关机的最长时间，超时后强制关闭
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(60);
+// `virsh domifaddr --source` 的查询顺序
+const ADDR_SOURCES: [&str; 3] = ["lease", "arp", "agent"];
+// 安卓x86虚拟机的adb默认端口
+const DEFAULT_ADB_PORT: u16 = 5555;
+
+/// libvirt管理的安卓虚拟机，通过virsh控制
+pub struct Libvirt {
+    domain: String,
+    /// libvirt连接地址，如 qemu:///system，未设置时使用virsh的默认连接
+    uri: Option<String>,
+    adb_port: u16,
+}
+
+impl Libvirt {
+    pub fn new(domain: String, uri: Option<String>, adb_port: Option<u16>) -> Self {
+        Self {
+            domain,
+            uri,
+            adb_port: adb_port.unwrap_or(DEFAULT_ADB_PORT),
+        }
+    }
+
+    async fn virsh(&self, args: &[&str]) -> Result<String, EmulatorError> {
+        let mut cmd = Command::new("virsh");
+        if let Some(uri) = &self.uri {
+            cmd.arg("-c").arg(uri);
+        }
+        let output = cmd
+            .args(args)
+            .arg(&self.domain)
+            .output()
+            .await
+            .map_err(|e| EmulatorError::new("找不到virsh命令", e.to_string()))?;
+        if !output.status.success() {
+            return Err(EmulatorError::new(
+                format!("virsh {}执行失败", args.join(" ")),
+                String::from_utf8_lossy(&output.stderr).trim(),
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// 虚拟机状态，如 running、shut off
+    async fn state(&self) -> Result<String, EmulatorError> {
+        self.virsh(&["domstate"]).await
+    }
+}
+
+/// `virsh domifaddr` 输出中的第一个IPv4地址，不带前缀长度
+fn first_ipv4(output: &str) -> Option<&str> {
+    output.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [_, _, "ipv4", addr, ..] => addr.split('/').next(),
+            _ => None,
+        }
+    })
+}
+
+impl Emulator for Libvirt {
+    fn label(&self) -> String {
+        format!("虚拟机{}", self.domain)
+    }
+
+    fn programs(&self) -> Vec<&'static str> {
+        vec!["virsh"]
+    }
+
+    fn check(&self) -> EmulatorFuture<'_> {
+        Box::pin(async move {
+            let state = self.state().await?;
+            log::info!("已找到虚拟机{}，当前状态: {}", self.domain, state);
+            Ok(())
+        })
+    }
+
+    fn start(&self) -> EmulatorFuture<'_> {
+        Box::pin(async move {
+            if self.state().await? == "running" {
+                log::info!("虚拟机已处于运行状态，无需启动");
+                return Ok(());
+            }
+            self.virsh(&["start"]).await?;
+            log::info!("虚拟机已启动");
+            Ok(())
+        })
+    }
+
+    fn stop(&self) -> EmulatorFuture<'_> {
+        Box::pin(async move {
+            if self.state().await? != "running" {
+                log::info!("虚拟机已处于关闭状态，无需关闭");
+                return Ok(());
+            }
+            self.virsh(&["shutdown"]).await?;
+            let mut waited = Duration::ZERO;
+            while waited < SHUTDOWN_TIMEOUT {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                waited += Duration::from_secs(2);
+                if self.state().await? != "running" {
+                    log::info!("虚拟机已关闭");
+                    return Ok(());
+                }
+            }
+            log::warn!(
+                "虚拟机{}秒内未能正常关机，强制关闭",
+                SHUTDOWN_TIMEOUT.as_secs()
+            );
+            self.virsh(&["destroy"]).await?;
+            Ok(())
+        })
+    }
+
+    /// 从 `virsh domifaddr` 的输出中取第一个IPv4地址
+    ///
+    /// 依次查询DHCP租约（libvirt的NAT网络）、宿主机ARP表（桥接网络）与虚拟机内的guest agent
+    fn adb_target(&self) -> Pin<Box<dyn Future<Output = Option<String>> + Send + '_>> {
+        Box::pin(async move {
+            for source in ADDR_SOURCES {
+                let Ok(output) = self.virsh(&["domifaddr", "--source", source]).await else {
+                    continue;
+                };
+                if let Some(ip) = first_ipv4(&output) {
+                    return Some(format!("{}:{}", ip, self.adb_port));
+                }
+            }
+            None
+        })
+    }
+}
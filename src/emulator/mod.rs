@@ -0,0 +1,73 @@
+mod compose;
+mod container;
+mod libvirt;
+mod waydroid;
+
+pub use compose::ComposeEnv;
+pub use container::Container;
+pub use libvirt::Libvirt;
+pub use waydroid::Waydroid;
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+/// 模拟器后端启动、关闭失败的信息
+#[derive(Debug)]
+pub struct EmulatorError {
+    /// 一句话说明
+    pub summary: String,
+    /// 命令的完整输出，用于诊断已知问题
+    pub log: String,
+    /// 是否需要把输出推送出去，compose的输出通常较长且包含多个服务
+    pub notify: bool,
+}
+
+impl EmulatorError {
+    pub fn new(summary: impl Into<String>, log: impl Into<String>) -> Self {
+        Self {
+            summary: summary.into(),
+            log: log.into(),
+            notify: false,
+        }
+    }
+}
+
+impl fmt::Display for EmulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.log.is_empty() {
+            write!(f, "{}", self.summary)
+        } else {
+            write!(f, "{}: {}", self.summary, self.log)
+        }
+    }
+}
+
+pub type EmulatorFuture<'a> = Pin<Box<dyn Future<Output = Result<(), EmulatorError>> + Send + 'a>>;
+
+/// 运行游戏的模拟器后端：podman容器、compose环境、Waydroid或libvirt虚拟机
+///
+/// 通过 EMULATOR_BACKEND 选择，未设置时配置了 COMPOSE_FILE 则为compose，否则为容器
+///
+/// 启动与关闭可能要等待几十秒（拉取镜像、等待会话就绪、等待虚拟机关机），都是异步的，不阻塞其它任务
+pub trait Emulator: Send + Sync {
+    /// 日志中显示的名称
+    fn label(&self) -> String;
+
+    /// 后端依赖的外部命令，启动前检查是否存在
+    fn programs(&self) -> Vec<&'static str>;
+
+    /// 检查后端是否可用，例如容器是否存在
+    fn check(&self) -> EmulatorFuture<'_>;
+
+    /// 启动模拟器，已经在运行时直接返回
+    fn start(&self) -> EmulatorFuture<'_>;
+
+    /// 关闭模拟器，已经停止时直接返回
+    fn stop(&self) -> EmulatorFuture<'_>;
+
+    /// 未配置 ADB_TARGET 时由后端推导adb地址，只有虚拟机等地址不固定的后端需要实现
+    fn adb_target(&self) -> Pin<Box<dyn Future<Output = Option<String>> + Send + '_>> {
+        Box::pin(async { None })
+    }
+}
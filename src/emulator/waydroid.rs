@@ -0,0 +1,125 @@
+use super::{Emulator, EmulatorError, EmulatorFuture};
+use crate::paths;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+// 等待Waydroid会话就绪的最长时间
+const SESSION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Waydroid会话，会话属于安装MAA的用户，需要该用户的图形会话
+pub struct Waydroid {
+    user_name: String,
+}
+
+impl Waydroid {
+    pub fn new(user_name: String) -> Self {
+        Self { user_name }
+    }
+
+    /// 以安装MAA的用户身份运行waydroid，使用sudo运行本工具时切换到该用户
+    fn command(&self) -> Command {
+        let mut cmd = Command::new("waydroid");
+        if let Some((uid, gid)) = paths::root_owner(&self.user_name) {
+            cmd.uid(uid)
+                .gid(gid)
+                .env("HOME", paths::user_home(&self.user_name))
+                .env("USER", &self.user_name)
+                .env("XDG_RUNTIME_DIR", format!("/run/user/{}", uid));
+        }
+        cmd
+    }
+
+    async fn session_running(&self) -> bool {
+        self.command()
+            .arg("status")
+            .output()
+            .await
+            .is_ok_and(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .any(|line| line.starts_with("Session:") && line.contains("RUNNING"))
+            })
+    }
+}
+
+impl Emulator for Waydroid {
+    fn label(&self) -> String {
+        "Waydroid".to_string()
+    }
+
+    fn programs(&self) -> Vec<&'static str> {
+        vec!["waydroid"]
+    }
+
+    fn check(&self) -> EmulatorFuture<'_> {
+        Box::pin(async move {
+            match self.command().arg("status").output().await {
+                Ok(output) if output.status.success() => {
+                    log::info!("已找到Waydroid");
+                    Ok(())
+                }
+                Ok(output) => Err(EmulatorError::new(
+                    "waydroid status执行失败",
+                    String::from_utf8_lossy(&output.stderr).trim(),
+                )),
+                Err(e) => Err(EmulatorError::new("找不到waydroid命令", e.to_string())),
+            }
+        })
+    }
+
+    fn start(&self) -> EmulatorFuture<'_> {
+        Box::pin(async move {
+            if self.session_running().await {
+                log::info!("Waydroid会话已在运行，无需启动");
+                return Ok(());
+            }
+            // session start会一直在前台运行，放到后台后轮询会话状态
+            self.command()
+                .arg("session")
+                .arg("start")
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|e| EmulatorError::new("Waydroid会话启动失败", e.to_string()))?;
+            let mut waited = Duration::ZERO;
+            while waited < SESSION_TIMEOUT {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                waited += Duration::from_secs(2);
+                if self.session_running().await {
+                    log::info!("Waydroid会话已启动");
+                    return Ok(());
+                }
+            }
+            Err(EmulatorError::new(
+                format!("Waydroid会话{}秒内未能启动", SESSION_TIMEOUT.as_secs()),
+                "",
+            ))
+        })
+    }
+
+    fn stop(&self) -> EmulatorFuture<'_> {
+        Box::pin(async move {
+            if !self.session_running().await {
+                log::info!("Waydroid会话已停止，无需关闭");
+                return Ok(());
+            }
+            let output = self
+                .command()
+                .arg("session")
+                .arg("stop")
+                .output()
+                .await
+                .map_err(|e| EmulatorError::new("Waydroid会话关闭失败", e.to_string()))?;
+            if !output.status.success() {
+                return Err(EmulatorError::new(
+                    "Waydroid会话关闭失败",
+                    String::from_utf8_lossy(&output.stderr).trim(),
+                ));
+            }
+            log::info!("Waydroid会话已关闭");
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,30 @@
+use std::process;
+
+/// 进程退出码约定，方便脚本按退出码分支处理
+///
+/// 通知推送失败只记录日志，不影响退出码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// 子命令的检查未通过或操作失败（doctor、sync-time、prune、artifacts）
+    CommandFailed = 1,
+    /// 配置缺失或配置有误，或状态目录无法写入
+    Config = 2,
+    /// 找不到运行Arknights的容器，或容器/compose环境启动失败
+    ContainerMissing = 3,
+    /// MAA启动失败或运行失败
+    MaaFailed = 4,
+    /// 已有MAA任务正在运行，拒绝重复启动
+    AlreadyRunning = 5,
+    /// adb无法启动，或重试后仍无法连接模拟器
+    AdbFailed = 6,
+    /// 游戏进程未能就绪
+    GameNotReady = 7,
+    /// MAA运行中收到Ctrl-C或SIGTERM被取消，与shell中断的惯例一致
+    Cancelled = 130,
+}
+
+impl ExitCode {
+    pub fn exit(self) -> ! {
+        process::exit(self as i32)
+    }
+}
@@ -0,0 +1,173 @@
+use chrono::{DateTime, Duration, Local};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+// 每行一次失败: `时间\t退出码\t类别\t指纹\t关键行`
+const RECORD_FILE: &str = "failures.tsv";
+// 只保留并统计最近30天的失败
+const RETENTION_DAYS: i64 = 30;
+// 输出最常见的失败类数
+const TOP_CLUSTERS: usize = 5;
+
+/// 一次失败的指纹：错误类别+退出码+关键行的哈希，关键行中的数字（时间、PID等）不参与计算
+struct Record {
+    time: DateTime<Local>,
+    exit_code: i32,
+    category: String,
+    fingerprint: String,
+    sample: String,
+}
+
+impl Record {
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(5, '\t');
+        Some(Self {
+            time: DateTime::parse_from_rfc3339(fields.next()?)
+                .ok()?
+                .with_timezone(&Local),
+            exit_code: fields.next()?.parse().ok()?,
+            category: fields.next()?.to_string(),
+            fingerprint: fields.next()?.to_string(),
+            sample: fields.next().unwrap_or_default().to_string(),
+        })
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}",
+            self.time.to_rfc3339(),
+            self.exit_code,
+            self.category,
+            self.fingerprint,
+            self.sample
+        )
+    }
+}
+
+/// 去掉日志前缀并把数字替换为#，使同一类错误得到相同的指纹
+fn normalize(line: &str) -> String {
+    let mut text = line.trim();
+    while let Some(rest) = text.strip_prefix('[').and_then(|t| t.split_once(']')) {
+        text = rest.1.trim_start();
+    }
+    text.chars()
+        .map(|c| if c.is_ascii_digit() { '#' } else { c })
+        .collect()
+}
+
+// FNV-1a，指纹会写入文件，需要在不同版本间保持稳定
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn load(state_dir: &Path) -> Vec<Record> {
+    let since = Local::now() - Duration::days(RETENTION_DAYS);
+    fs::read_to_string(state_dir.join(RECORD_FILE))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(Record::parse)
+        .filter(|record| record.time >= since)
+        .collect()
+}
+
+/// 记录一次失败，同时清理超过保留期的记录
+pub fn record(
+    state_dir: &Path,
+    exit_code: i32,
+    category: &str,
+    key_line: Option<&str>,
+) -> io::Result<()> {
+    let sample = key_line.map(normalize).unwrap_or_default();
+    let fingerprint = format!(
+        "{:016x}",
+        fnv1a(&format!("{}|{}|{}", category, exit_code, sample))
+    );
+    let mut records = load(state_dir);
+    records.push(Record {
+        time: Local::now(),
+        exit_code,
+        category: category.replace('\t', " "),
+        fingerprint,
+        sample: sample.replace('\t', " "),
+    });
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(state_dir.join(RECORD_FILE))?;
+    for record in &records {
+        writeln!(file, "{}", record.to_line())?;
+    }
+    Ok(())
+}
+
+/// `easy_maa failures`：输出最近30天最常见的几类失败
+pub fn print_clusters(state_dir: &Path) {
+    let records = load(state_dir);
+    if records.is_empty() {
+        println!("最近{}天没有失败记录", RETENTION_DAYS);
+        return;
+    }
+    let mut clusters: HashMap<&str, Vec<&Record>> = HashMap::new();
+    for record in &records {
+        clusters
+            .entry(&record.fingerprint)
+            .or_default()
+            .push(record);
+    }
+    let mut clusters: Vec<Vec<&Record>> = clusters.into_values().collect();
+    clusters.sort_by_key(|cluster| Reverse(cluster.len()));
+    println!(
+        "最近{}天共失败{}次，最常见的失败:",
+        RETENTION_DAYS,
+        records.len()
+    );
+    for (i, cluster) in clusters.iter().take(TOP_CLUSTERS).enumerate() {
+        let last = cluster.iter().map(|record| record.time).max().unwrap();
+        let first = cluster[0];
+        println!(
+            "{}. {} (退出码{})  {}次  最近一次: {}",
+            i + 1,
+            first.category,
+            first.exit_code,
+            cluster.len(),
+            last.format("%Y-%m-%d %H:%M")
+        );
+        if !first.sample.is_empty() {
+            println!("   {}", first.sample);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_strips_prefixes_and_digits() {
+        assert_eq!(
+            normalize("[2026-10-14 04:16:21][ERROR] [Fight]  Failed to connect to 127.0.0.1:5555 "),
+            "Failed to connect to ###.#.#.#:####"
+        );
+    }
+
+    #[test]
+    fn normalize_gives_same_fingerprint_to_same_error() {
+        let first = normalize("[04:00:01][ERROR] timeout after 30s, retry 1");
+        let second = normalize("[05:12:44][ERROR] timeout after 45s, retry 2");
+        assert_eq!(first, second);
+        assert_eq!(fnv1a(&first), fnv1a(&second));
+    }
+
+    #[test]
+    fn normalize_keeps_text_without_prefix() {
+        assert_eq!(normalize("游戏未就绪"), "游戏未就绪");
+        // 不成对的方括号不是前缀
+        assert_eq!(normalize("[ERROR missing"), "[ERROR missing");
+    }
+}
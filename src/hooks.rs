@@ -0,0 +1,105 @@
+use crate::notifier::{self, NotifyEvent};
+use crate::paths::PathResolver;
+use chrono::Local;
+use serde_json::json;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+// 钩子脚本的默认超时
+const DEFAULT_TIMEOUT: u64 = 60;
+
+// 解析脚本路径用的解析器，与其它配置项相同
+static RESOLVER: OnceLock<PathResolver> = OnceLock::new();
+// 本次运行的RUN_ID与工作目录，创建后传给钩子脚本
+static RUN_DIR: OnceLock<(String, PathBuf)> = OnceLock::new();
+
+/// 登记解析配置路径用的解析器，HOOK_* 中的 ~ 与相对路径和其它配置项一样解析
+pub fn set_resolver(resolver: PathResolver) {
+    let _ = RESOLVER.set(resolver);
+}
+
+/// 登记本次运行的工作目录，之后的钩子脚本通过 RUN_ID、RUN_DIR 环境变量与JSON字段拿到它
+pub fn set_run_dir(run_id: &str, dir: &Path) {
+    let _ = RUN_DIR.set((run_id.to_string(), dir.to_path_buf()));
+}
+
+/// 执行 HOOK_<事件> 配置的外部脚本，事件数据以JSON写入脚本的stdin，输出记录到日志
+///
+/// 脚本失败或超时只记录日志，不影响主流程
+pub async fn run(event: NotifyEvent, detail: Option<&str>) {
+    let Some(raw) = env::var(format!("HOOK_{}", event.key()))
+        .ok()
+        .filter(|script| !script.trim().is_empty())
+    else {
+        return;
+    };
+    let Some(resolver) = RESOLVER.get() else {
+        log::error!("钩子脚本 {} 未执行: 配置尚未加载", raw.trim());
+        return;
+    };
+    let script = resolver.resolve(raw.trim());
+    let payload = json!({
+        "event": event.key().to_lowercase(),
+        "title": notifier::title(),
+        "text": event.text(),
+        "detail": detail,
+        "timestamp": Local::now().to_rfc3339(),
+        "run_id": RUN_DIR.get().map(|(id, _)| id),
+        "run_dir": RUN_DIR.get().map(|(_, dir)| dir),
+    });
+    let timeout = env::var("HOOK_TIMEOUT")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_TIMEOUT);
+
+    let mut command = Command::new(&script);
+    if let Some((run_id, dir)) = RUN_DIR.get() {
+        command.env("RUN_ID", run_id).env("RUN_DIR", dir);
+    }
+    let mut child = match command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            log::error!("钩子脚本 {} 启动失败: {}", script.display(), e);
+            return;
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(payload.to_string().as_bytes()).await;
+    }
+    let output =
+        match tokio::time::timeout(Duration::from_secs(timeout), child.wait_with_output()).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => {
+                log::error!("钩子脚本 {} 执行失败: {}", script.display(), e);
+                return;
+            }
+            Err(_) => {
+                log::error!(
+                    "钩子脚本 {} 超过{}秒未结束，已终止",
+                    script.display(),
+                    timeout
+                );
+                return;
+            }
+        };
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        log::info!(target: "hook", "{}", line);
+    }
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        log::warn!(target: "hook", "{}", line);
+    }
+    if !output.status.success() {
+        log::error!("钩子脚本 {} 退出状态: {}", script.display(), output.status);
+    }
+}
@@ -0,0 +1,30 @@
+use std::env;
+use std::time::Duration;
+
+// 未配置 HTTP_TIMEOUT 时单个请求的超时（秒）
+const DEFAULT_TIMEOUT: u64 = 15;
+
+/// 推送通知、下载规则包与日历共用的HTTP客户端，请求超时由 HTTP_TIMEOUT 配置
+///
+/// 这些请求有的在持有运行锁时发出，不设超时的话服务器无响应会让整次运行卡住
+pub fn client() -> reqwest::Client {
+    let timeout = env::var("HTTP_TIMEOUT")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_TIMEOUT);
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(timeout))
+        .build()
+        .unwrap_or_default()
+}
+
+/// GET请求并返回响应正文，非2xx响应视为失败
+pub async fn get_text(url: &str) -> Result<String, reqwest::Error> {
+    client()
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await
+}
@@ -0,0 +1,104 @@
+use std::io::{self, Write};
+use std::os::unix::net::UnixDatagram;
+use tracing::{Level, Metadata};
+use tracing_subscriber::fmt::MakeWriter;
+
+// journald原生协议的socket
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+const SYSLOG_IDENTIFIER: &str = "easy_maa";
+
+/// `--journald` 时把日志同时写入journald，每条日志带 SYSLOG_IDENTIFIER 与本次运行的 RUN_ID
+///
+/// 可通过 `journalctl -t easy_maa RUN_ID=<id>` 检索某一次运行的日志
+pub struct Journald {
+    socket: Option<UnixDatagram>,
+    run_id: String,
+}
+
+impl Journald {
+    pub fn new(run_id: String) -> Self {
+        let socket = UnixDatagram::unbound()
+            .and_then(|socket| socket.connect(JOURNAL_SOCKET).map(|_| socket));
+        if let Err(e) = &socket {
+            eprintln!("连接journald失败，日志不会写入journald: {}", e);
+        }
+        Self {
+            socket: socket.ok(),
+            run_id,
+        }
+    }
+
+    pub fn connected(&self) -> bool {
+        self.socket.is_some()
+    }
+}
+
+/// 一条日志，fmt层写完后在drop时作为一个数据报发给journald
+pub struct JournalEntry<'a> {
+    journald: &'a Journald,
+    priority: u8,
+    message: Vec<u8>,
+}
+
+impl<'a> MakeWriter<'a> for Journald {
+    type Writer = JournalEntry<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.entry(6)
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        // syslog优先级: 3错误 4警告 6信息 7调试
+        let priority = match *meta.level() {
+            Level::ERROR => 3,
+            Level::WARN => 4,
+            Level::INFO => 6,
+            Level::DEBUG | Level::TRACE => 7,
+        };
+        self.entry(priority)
+    }
+}
+
+impl Journald {
+    fn entry(&self, priority: u8) -> JournalEntry<'_> {
+        JournalEntry {
+            journald: self,
+            priority,
+            message: Vec::new(),
+        }
+    }
+}
+
+impl Write for JournalEntry<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.message.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for JournalEntry<'_> {
+    fn drop(&mut self) {
+        let Some(socket) = &self.journald.socket else {
+            return;
+        };
+        let message = self.message.trim_ascii_end();
+        if message.is_empty() {
+            return;
+        }
+        let mut datagram = format!(
+            "PRIORITY={}\nSYSLOG_IDENTIFIER={}\nRUN_ID={}\n",
+            self.priority, SYSLOG_IDENTIFIER, self.journald.run_id
+        )
+        .into_bytes();
+        // MESSAGE可能包含换行，使用带长度前缀的二进制格式
+        datagram.extend_from_slice(b"MESSAGE\n");
+        datagram.extend_from_slice(&(message.len() as u64).to_le_bytes());
+        datagram.extend_from_slice(message);
+        datagram.push(b'\n');
+        let _ = socket.send(&datagram);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::diagnose::{self, Diagnosis};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+// 检查日志文件新内容的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+// 失败取证时读取的日志尾部行数，以及附在通知里的错误行数
+const FORENSIC_LINES: usize = 200;
+const REPORTED_ERROR_LINES: usize = 10;
+
+/// maa-cli 默认写入的日志文件
+pub fn default_path(user_home: &Path) -> PathBuf {
+    user_home.join(".local/state/maa/debug/asst.log")
+}
+
+/// 日志文件当前的长度，文件不存在时为0；在启动MAA前记录，之后只看本次运行新写入的内容
+pub fn log_len(path: &Path) -> u64 {
+    path.metadata().map(|meta| meta.len()).unwrap_or(0)
+}
+
+/// 后台跟踪MAA日志文件，把警告和错误行合并进本工具的日志（target为maa_logfile）
+pub struct LogTail {
+    stop: oneshot::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl LogTail {
+    /// 从文件当前末尾开始跟踪，只关注本次运行新写入的内容
+    pub fn spawn(path: PathBuf) -> Self {
+        let (stop, mut stopped) = oneshot::channel();
+        let handle = tokio::spawn(async move {
+            let mut reader = TailReader::new(path);
+            loop {
+                reader.read_new_lines();
+                tokio::select! {
+                    _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                    _ = &mut stopped => break,
+                }
+            }
+            // 停止前再读一次，避免漏掉MAA退出前最后写入的内容
+            reader.read_new_lines();
+        });
+        Self { stop, handle }
+    }
+
+    pub async fn stop(self) {
+        let _ = self.stop.send(());
+        let _ = self.handle.await;
+    }
+}
+
+struct TailReader {
+    path: PathBuf,
+    offset: u64,
+    partial: String,
+}
+
+impl TailReader {
+    fn new(path: PathBuf) -> Self {
+        let offset = log_len(&path);
+        Self {
+            path,
+            offset,
+            partial: String::new(),
+        }
+    }
+
+    fn read_new_lines(&mut self) {
+        let Ok(mut file) = File::open(&self.path) else {
+            return;
+        };
+        let len = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+        // 文件被截断或轮转后从头读取
+        if len < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        let mut buf = Vec::new();
+        if file.seek(SeekFrom::Start(self.offset)).is_err() || file.read_to_end(&mut buf).is_err() {
+            return;
+        }
+        self.offset += buf.len() as u64;
+        self.partial.push_str(&String::from_utf8_lossy(&buf));
+        // 最后一段可能还没写完整，留到下次再处理
+        let complete = match self.partial.rfind('\n') {
+            Some(pos) => self.partial.drain(..=pos).collect::<String>(),
+            None => return,
+        };
+        for line in complete.lines() {
+            forward_line(line);
+        }
+    }
+}
+
+/// asst.log 的行格式为 `[时间][级别][Px进程][Tx线程] 内容`，只转发警告和错误
+fn forward_line(line: &str) {
+    if line.contains("[ERR]") {
+        log::error!(target: "maa_logfile", "{}", line);
+    } else if line.contains("[WRN]") {
+        log::warn!(target: "maa_logfile", "{}", line);
+    }
+}
+
+/// MAA失败后从日志尾部取证的结果
+pub struct FailureReport {
+    /// 匹配到的已知错误
+    pub diagnosis: Option<Diagnosis>,
+    /// 最近的错误行
+    pub errors: Vec<String>,
+}
+
+impl fmt::Display for FailureReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(diagnosis) = &self.diagnosis {
+            write!(f, "失败原因: {}\n\n", diagnosis)?;
+        }
+        write!(f, "```\n{}\n```", self.errors.join("\n"))
+    }
+}
+
+/// MAA失败后从日志尾部取证：匹配已知错误给出失败原因并附上最近的错误行，没有错误行时返回None
+///
+/// 只看 `offset`（启动MAA前的文件长度）之后写入的内容，避免MAA没来得及写日志时把上次运行的错误当作这次的；
+/// 文件比 `offset` 短说明已被轮转，从头读取
+pub fn failure_report(path: &Path, offset: u64) -> Option<FailureReport> {
+    let content = fs::read(path).ok()?;
+    let start = usize::try_from(offset)
+        .ok()
+        .filter(|start| *start <= content.len())
+        .unwrap_or(0);
+    let content = String::from_utf8_lossy(&content[start..]);
+    let lines: Vec<&str> = content.lines().collect();
+    let errors: Vec<&str> = lines[lines.len().saturating_sub(FORENSIC_LINES)..]
+        .iter()
+        .copied()
+        .filter(|line| line.contains("[ERR]"))
+        .collect();
+    if errors.is_empty() {
+        return None;
+    }
+    let errors = &errors[errors.len().saturating_sub(REPORTED_ERROR_LINES)..];
+    let diagnosis = diagnose::diagnose_maa_log(&errors.join("\n"));
+    if let Some(diagnosis) = &diagnosis {
+        log::error!("{}", diagnosis);
+    }
+    Some(FailureReport {
+        diagnosis,
+        errors: errors.iter().map(|line| line.to_string()).collect(),
+    })
+}
@@ -0,0 +1,387 @@
+use crate::maa_rules::ParserRules;
+use crate::notifier::{self, NotifyEvent};
+use crate::progress::Progress;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+// 通知里最多附带的错误行数
+const MAX_ERRORS: usize = 10;
+// 通知里最多附带的基建结果行数
+const MAX_INFRAST: usize = 5;
+// 通知里列出的掉落物品数
+const TOP_DROPS: usize = 3;
+
+/// 从maa-cli输出中解析出的运行进度
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct MaaProgress {
+    /// 正在执行的任务
+    pub current_task: Option<String>,
+    pub completed_tasks: Vec<String>,
+    pub failed_tasks: Vec<String>,
+    /// 最近一次作战的关卡
+    pub fight_stage: Option<String>,
+    pub fight_times: u32,
+    pub sanity_used: u32,
+    /// 公招结果行
+    pub recruits: Vec<String>,
+    /// 各物品的掉落总数
+    pub drops: BTreeMap<String, u32>,
+    /// 基建结果行
+    pub infrast: Vec<String>,
+    pub errors: Vec<String>,
+    /// 读取的输出行数与其中命中解析规则的行数，用于发现规则是否已经失效
+    pub lines: u32,
+    pub matched_lines: u32,
+    /// 本次运行已经提醒过的关键字，每个关键字只提醒一次
+    #[serde(skip)]
+    alerted_keywords: Vec<String>,
+    /// 上一次累计掉落统计之后逐次加到drops里的数量，出现新的累计统计时被替换
+    #[serde(skip)]
+    uncounted_drops: BTreeMap<String, u32>,
+}
+
+impl MaaProgress {
+    /// 解析一行输出，进度有变化时返回true
+    pub fn parse_line(&mut self, rules: &ParserRules, line: &str) -> bool {
+        let raw = line.trim();
+        let text = rules.prefix.replace(raw, "");
+        let text = text.trim();
+        if text.is_empty() {
+            return false;
+        }
+        self.lines += 1;
+        let changed = self.apply(rules, raw, text);
+        if changed {
+            self.matched_lines += 1;
+        }
+        changed
+    }
+
+    // 规则包里的命名分组可能是可选的，匹配时没有取到分组按未命中处理
+    fn apply(&mut self, rules: &ParserRules, raw: &str, text: &str) -> bool {
+        if let Some(caps) = rules.task.captures(text)
+            && let (Some(task), Some(status)) = (caps.name("task"), caps.name("status"))
+        {
+            let task = task.as_str().to_string();
+            match status.as_str() {
+                "Start" => self.current_task = Some(task),
+                "Completed" => {
+                    self.current_task = None;
+                    self.completed_tasks.push(task);
+                }
+                _ => {
+                    self.current_task = None;
+                    self.failed_tasks.push(task);
+                }
+            }
+            return true;
+        }
+        let mut changed = false;
+        if let Some(caps) = rules.fight.captures(text)
+            && let (Some(stage), Some(times)) = (caps.name("stage"), caps.name("times"))
+        {
+            self.fight_stage = Some(stage.as_str().to_string());
+            self.fight_times += times.as_str().parse::<u32>().unwrap_or(0);
+            changed = true;
+        }
+        if let Some(sanity) = rules
+            .sanity
+            .captures(text)
+            .and_then(|caps| caps.name("sanity"))
+        {
+            self.sanity_used += sanity.as_str().parse::<u32>().unwrap_or(0);
+            changed = true;
+        }
+        if rules.recruit.is_match(text) {
+            self.recruits.push(text.to_string());
+            changed = true;
+        }
+        // 累计统计行的数量已经包含了之前逐次作战的掉落，不能再叠加
+        let total = rules.drop_total.is_match(text);
+        if total {
+            for (item, count) in std::mem::take(&mut self.uncounted_drops) {
+                if let Some(sum) = self.drops.get_mut(&item) {
+                    *sum = sum.saturating_sub(count);
+                }
+            }
+            self.drops.retain(|_, count| *count > 0);
+        }
+        let drops = rules
+            .drop
+            .captures_iter(text)
+            .filter_map(|caps| Some((caps.name("item")?, caps.name("count")?)));
+        for (item, count) in drops {
+            let count = count.as_str().parse::<u32>().unwrap_or(0);
+            let item = item.as_str().to_string();
+            if !total {
+                *self.uncounted_drops.entry(item.clone()).or_default() += count;
+            }
+            *self.drops.entry(item).or_default() += count;
+            changed = true;
+        }
+        if rules.infrast.is_match(text) && self.infrast.len() < MAX_INFRAST {
+            self.infrast.push(text.to_string());
+            changed = true;
+        }
+        if rules.error.is_match(raw) && self.errors.len() < MAX_ERRORS {
+            self.errors.push(text.to_string());
+            changed = true;
+        }
+        changed
+    }
+
+    /// 运行总结，附在最终的通知里；没有解析到任何内容时返回None
+    pub fn summary(&self) -> Option<String> {
+        let mut lines = Vec::new();
+        if !self.completed_tasks.is_empty() {
+            lines.push(format!("已完成任务: {}", self.completed_tasks.join(", ")));
+        }
+        if !self.failed_tasks.is_empty() {
+            lines.push(format!("失败任务: {}", self.failed_tasks.join(", ")));
+        }
+        if let Some(stage) = &self.current_task {
+            lines.push(format!("中断于: {}", stage));
+        }
+        if self.fight_times > 0 || self.sanity_used > 0 {
+            lines.push(format!(
+                "作战: {} 共{}次，消耗理智{}",
+                self.fight_stage.as_deref().unwrap_or("未知关卡"),
+                self.fight_times,
+                self.sanity_used
+            ));
+        }
+        if !self.drops.is_empty() {
+            let mut drops: Vec<(&String, &u32)> = self.drops.iter().collect();
+            drops.sort_by_key(|(_, count)| Reverse(**count));
+            let top: Vec<String> = drops
+                .iter()
+                .take(TOP_DROPS)
+                .map(|(item, count)| format!("{}×{}", item, count))
+                .collect();
+            lines.push(format!("主要掉落: {}", top.join(", ")));
+        }
+        for infrast in &self.infrast {
+            lines.push(format!("基建: {}", infrast));
+        }
+        for recruit in &self.recruits {
+            lines.push(format!("公招: {}", recruit));
+        }
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+}
+
+/// 完成后可推送里程碑通知的任务
+fn milestone_event(task: &str) -> Option<NotifyEvent> {
+    match task {
+        "Fight" => Some(NotifyEvent::FightDone),
+        "Infrast" => Some(NotifyEvent::InfrastDone),
+        _ => None,
+    }
+}
+
+/// 运行中要推送的里程碑与关键字提醒
+pub type Alert = (NotifyEvent, Option<String>);
+
+/// 在单独的任务里依次推送运行中的提醒
+///
+/// 推送（含钩子脚本）可能耗时较久，放在读取输出的循环里会让管道写满、阻塞MAA；
+/// 所有发送端关闭后任务结束，应在读取输出的任务结束后等待它
+pub fn spawn_alerts() -> (mpsc::UnboundedSender<Alert>, JoinHandle<()>) {
+    let (sender, mut alerts) = mpsc::unbounded_channel::<Alert>();
+    let handle = tokio::spawn(async move {
+        while let Some((event, detail)) = alerts.recv().await {
+            notifier::notify(event, detail.as_deref()).await;
+        }
+    });
+    (sender, handle)
+}
+
+/// 逐行读取MAA的输出：原样写到echo，同时更新进度并在变化时输出进度事件，
+/// 任务完成时发出里程碑提醒，出现订阅的关键字时发出关键字提醒
+pub fn spawn_reader<R, W>(
+    reader: R,
+    mut echo: W,
+    rules: Arc<ParserRules>,
+    keywords: Arc<Vec<String>>,
+    state: Arc<Mutex<MaaProgress>>,
+    progress: Progress,
+    alerts: mpsc::UnboundedSender<Alert>,
+) -> JoinHandle<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: Write + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(reader);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            let _ = echo.write_all(&buf);
+            let _ = echo.flush();
+            let line = String::from_utf8_lossy(&buf);
+            let (milestone, matched) = {
+                let mut state = state.lock().unwrap();
+                // 一行可能包含多个关键字，已经提醒过的关键字不影响其他关键字
+                let matched: Vec<String> = keywords
+                    .iter()
+                    .filter(|keyword| {
+                        line.contains(keyword.as_str()) && !state.alerted_keywords.contains(keyword)
+                    })
+                    .cloned()
+                    .collect();
+                state.alerted_keywords.extend(matched.iter().cloned());
+                let completed = state.completed_tasks.len();
+                if state.parse_line(&rules, &line) {
+                    progress.maa_progress(&state);
+                }
+                // 同一任务只在第一次完成时推送
+                let milestone = state.completed_tasks[completed..]
+                    .iter()
+                    .filter(|task| state.completed_tasks.iter().filter(|t| t == task).count() == 1)
+                    .find_map(|task| milestone_event(task));
+                (milestone, matched)
+            };
+            for keyword in matched {
+                let detail = format!("关键字「{}」: {}", keyword, line.trim());
+                log::warn!("{}", detail);
+                let _ = alerts.send((NotifyEvent::KeywordAlert, Some(detail)));
+            }
+            if let Some(event) = milestone.filter(|event| notifier::milestone_enabled(*event)) {
+                let _ = alerts.send((event, None));
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // maa-cli一次日常运行的输出（节选）
+    const SAMPLE: &str = "\
+[2026-10-14 04:00:01][INFO] StartUp Start
+[2026-10-14 04:00:20][INFO] Connecting to localhost:5555
+[2026-10-14 04:00:21]
+[2026-10-14 04:00:35][INFO] StartUp Completed
+[2026-10-14 04:00:36][INFO] Fight Start
+[2026-10-14 04:12:10][INFO] Fight 1-7 3 times, used 18 sanity, drops:
+[2026-10-14 04:12:10][INFO] 1. 固源岩 × 2, 源岩 × 1
+[2026-10-14 04:12:10][INFO] 2. 固源岩 × 3
+[2026-10-14 04:12:10][INFO] 3. 固源岩 × 1, 装置 × 1
+[2026-10-14 04:12:10][INFO] total drops: 固源岩 × 6, 源岩 × 1, 装置 × 1
+[2026-10-14 04:12:11][INFO] Fight Completed
+[2026-10-14 04:12:12][INFO] Infrast Start
+[2026-10-14 04:15:40][INFO] Infrast: 制造站 3, 贸易站 2, 宿舍 4
+[2026-10-14 04:15:41][INFO] Infrast Completed
+[2026-10-14 04:15:42][INFO] Recruit Start
+[2026-10-14 04:16:20][INFO] Recruit tags: 高级资深干员, 近卫干员 (6 ★)
+[2026-10-14 04:16:21][ERROR] Image recognition timed out after 30s
+[2026-10-14 04:16:22][ERROR] Recruit Error
+[2026-10-14 04:16:23][INFO] Award Start
+";
+
+    fn parse(output: &str) -> MaaProgress {
+        let rules = ParserRules::builtin();
+        let mut progress = MaaProgress::default();
+        for line in output.lines() {
+            progress.parse_line(&rules, line);
+        }
+        progress
+    }
+
+    #[test]
+    fn parses_sample_output() {
+        let progress = parse(SAMPLE);
+        assert_eq!(progress.completed_tasks, ["StartUp", "Fight", "Infrast"]);
+        assert_eq!(progress.failed_tasks, ["Recruit"]);
+        assert_eq!(progress.current_task.as_deref(), Some("Award"));
+        assert_eq!(progress.fight_stage.as_deref(), Some("1-7"));
+        assert_eq!(progress.fight_times, 3);
+        assert_eq!(progress.sanity_used, 18);
+        assert_eq!(
+            progress.recruits,
+            ["Recruit tags: 高级资深干员, 近卫干员 (6 ★)"]
+        );
+        assert_eq!(progress.infrast, ["Infrast: 制造站 3, 贸易站 2, 宿舍 4"]);
+        assert_eq!(progress.errors, ["Image recognition timed out after 30s"]);
+        // 只有时间前缀的空行不计入
+        assert_eq!(progress.lines, 18);
+        assert_eq!(progress.matched_lines, 17);
+    }
+
+    #[test]
+    fn summary_of_sample_output() {
+        assert_eq!(
+            parse(SAMPLE).summary().as_deref(),
+            Some(
+                "已完成任务: StartUp, Fight, Infrast\n\
+                 失败任务: Recruit\n\
+                 中断于: Award\n\
+                 作战: 1-7 共3次，消耗理智18\n\
+                 主要掉落: 固源岩×6, 源岩×1, 装置×1\n\
+                 基建: Infrast: 制造站 3, 贸易站 2, 宿舍 4\n\
+                 公招: Recruit tags: 高级资深干员, 近卫干员 (6 ★)"
+            )
+        );
+    }
+
+    #[test]
+    fn summary_is_none_without_progress() {
+        assert_eq!(parse("[INFO] Connecting to localhost:5555").summary(), None);
+    }
+
+    #[test]
+    fn drop_totals_replace_per_fight_drops() {
+        let progress = parse(SAMPLE);
+        let drops: Vec<(&str, u32)> = progress
+            .drops
+            .iter()
+            .map(|(item, count)| (item.as_str(), *count))
+            .collect();
+        assert_eq!(drops, [("固源岩", 6), ("源岩", 1), ("装置", 1)]);
+    }
+
+    #[test]
+    fn optional_groups_in_rule_pack_do_not_match() {
+        let mut rules = ParserRules::builtin();
+        rules.task = regex::Regex::new(r"^(?P<task>\w+) (?:(?P<status>Start)|Done)").unwrap();
+        rules.sanity = regex::Regex::new(r"used (?:(?P<sanity>\d+) )?sanity").unwrap();
+        let mut progress = MaaProgress::default();
+        assert!(!progress.parse_line(&rules, "[INFO] Fight Done"));
+        assert!(!progress.parse_line(&rules, "[INFO] used sanity"));
+        assert!(progress.parse_line(&rules, "[INFO] Fight Start"));
+        assert_eq!(progress.current_task.as_deref(), Some("Fight"));
+        assert_eq!(progress.sanity_used, 0);
+    }
+
+    #[test]
+    fn drops_without_totals_are_summed() {
+        let progress = parse("[INFO] 1. 固源岩 × 2\n[INFO] 2. 固源岩 × 3, 源岩 × 1");
+        assert_eq!(progress.drops["固源岩"], 5);
+        assert_eq!(progress.drops["源岩"], 1);
+    }
+
+    #[test]
+    fn drop_totals_of_separate_fights_add_up() {
+        let progress = parse(
+            "\
+[INFO] 1. 固源岩 × 2
+[INFO] total drops: 固源岩 × 2
+[INFO] 1. 固源岩 × 1, 源岩 × 2
+[INFO] 2. 源岩 × 1
+[INFO] total drops: 固源岩 × 1, 源岩 × 3",
+        );
+        assert_eq!(progress.drops["固源岩"], 3);
+        assert_eq!(progress.drops["源岩"], 3);
+    }
+}
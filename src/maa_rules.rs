@@ -0,0 +1,182 @@
+use crate::http;
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+// 内置规则，外部规则包缺少某条规则或规则无效时回退到这里
+const BUILTIN_VERSION: &str = "builtin";
+const PREFIX: &str = r"^(\[[^\]]*\]\s*)+";
+const TASK: &str = r"^(?P<task>StartUp|CloseDown|Fight|Recruit|Infrast|Mall|Award|Roguelike|Copilot|SSSCopilot|Depot|OperBox|Reclamation|Custom|SingleStep|VideoRecognition)\s+(?P<status>Start|Completed|Error|Stopped)\b";
+const FIGHT: &str = r"(?i)^fight\s+(?P<stage>\S+)\s+(?P<times>\d+)\s+times?";
+const SANITY: &str = r"(?i)used\s+(?P<sanity>\d+)\s+sanity";
+const RECRUIT: &str = r"(?i)recruit.*(\d\s*★|\d\s*stars?|tags?)";
+// 掉落统计中的「物品 × 数量」，一行可以有多项
+const DROP: &str = r"(?P<item>[^\s\d,:：×][^\s,:：×]*)\s*×\s*(?P<count>\d+)";
+// 累计掉落统计行，如maa-cli作战总结末尾的 `total drops: ...`
+const DROP_TOTAL: &str = r"(?i)^total\s+drops?\b";
+const INFRAST: &str = r"(?i)^(infrast\b|基建).+";
+const ERROR: &str = r"\b(ERROR|ERR)\b";
+
+/// 规则包文件格式，各字段都是可选的正则，命名分组对应解析出的字段
+#[derive(Debug, Default, Deserialize)]
+struct RulePack {
+    version: Option<String>,
+    /// 行首需要去掉的前缀（时间、级别等）
+    prefix: Option<String>,
+    /// 需要命名分组task与status，status为Start/Completed/Error/Stopped
+    task: Option<String>,
+    /// 需要命名分组stage与times
+    fight: Option<String>,
+    /// 需要命名分组sanity
+    sanity: Option<String>,
+    recruit: Option<String>,
+    /// 需要命名分组item与count，在一行中重复匹配
+    drop: Option<String>,
+    /// 累计掉落统计行，该行的数量是至今的总数而不是单次作战的掉落
+    drop_total: Option<String>,
+    /// 基建收菜等结果行
+    infrast: Option<String>,
+    error: Option<String>,
+}
+
+/// 解析maa-cli输出所用的规则
+///
+/// MAA的输出格式会随版本变化，可通过 MAA_PARSER_RULES 指定JSON规则包（本地文件或http(s)地址）覆盖内置规则
+pub struct ParserRules {
+    pub version: String,
+    pub prefix: Regex,
+    pub task: Regex,
+    pub fight: Regex,
+    pub sanity: Regex,
+    pub recruit: Regex,
+    pub drop: Regex,
+    pub drop_total: Regex,
+    pub infrast: Regex,
+    pub error: Regex,
+}
+
+impl ParserRules {
+    pub fn builtin() -> Self {
+        Self::from_pack(RulePack::default())
+    }
+
+    /// 加载规则包；远程规则包下载并解析成功后缓存到 `cache`，下载失败或内容无效时使用上次的缓存，都不可用时使用内置规则
+    pub async fn load(source: Option<&str>, cache: &Path) -> Self {
+        let Some(source) = source else {
+            return Self::builtin();
+        };
+        let pack = if source.starts_with("http://") || source.starts_with("https://") {
+            let downloaded = fetch(source)
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|content| parse_pack(&content).map(|pack| (pack, content)));
+            match downloaded {
+                Ok((pack, content)) => {
+                    // 只缓存有效的规则包，避免坏的远程规则包覆盖上次可用的缓存
+                    if let Err(e) = fs::write(cache, &content) {
+                        log::warn!("缓存解析规则包失败: {}", e);
+                    }
+                    Ok(pack)
+                }
+                Err(e) => {
+                    log::warn!("远程解析规则包{}不可用: {}，尝试使用缓存", source, e);
+                    fs::read_to_string(cache)
+                        .map_err(|e| e.to_string())
+                        .and_then(|content| parse_pack(&content))
+                }
+            }
+        } else {
+            fs::read_to_string(source)
+                .map_err(|e| e.to_string())
+                .and_then(|content| parse_pack(&content))
+        };
+        match pack {
+            Ok(pack) => {
+                let rules = Self::from_pack(pack);
+                log::info!("已加载MAA输出解析规则包，版本: {}", rules.version);
+                rules
+            }
+            Err(e) => {
+                log::warn!("解析规则包{}不可用: {}，使用内置规则", source, e);
+                Self::builtin()
+            }
+        }
+    }
+
+    fn from_pack(pack: RulePack) -> Self {
+        Self {
+            version: pack.version.unwrap_or_else(|| BUILTIN_VERSION.to_string()),
+            prefix: compile("prefix", pack.prefix, PREFIX, &[]),
+            task: compile("task", pack.task, TASK, &["task", "status"]),
+            fight: compile("fight", pack.fight, FIGHT, &["stage", "times"]),
+            sanity: compile("sanity", pack.sanity, SANITY, &["sanity"]),
+            recruit: compile("recruit", pack.recruit, RECRUIT, &[]),
+            drop: compile("drop", pack.drop, DROP, &["item", "count"]),
+            drop_total: compile("drop_total", pack.drop_total, DROP_TOTAL, &[]),
+            infrast: compile("infrast", pack.infrast, INFRAST, &[]),
+            error: compile("error", pack.error, ERROR, &[]),
+        }
+    }
+}
+
+fn parse_pack(content: &str) -> Result<RulePack, String> {
+    serde_json::from_str(content).map_err(|e| e.to_string())
+}
+
+/// 编译规则包中的一条规则，规则无效或缺少需要的命名分组时回退到内置规则
+fn compile(name: &str, custom: Option<String>, builtin: &str, groups: &[&str]) -> Regex {
+    if let Some(pattern) = custom {
+        match Regex::new(&pattern) {
+            Ok(regex) => {
+                let names: Vec<&str> = regex.capture_names().flatten().collect();
+                if let Some(missing) = groups.iter().find(|group| !names.contains(group)) {
+                    log::warn!("解析规则{}缺少命名分组{}，使用内置规则", name, missing);
+                } else {
+                    return regex;
+                }
+            }
+            Err(e) => log::warn!("解析规则{}无效: {}，使用内置规则", name, e),
+        }
+    }
+    Regex::new(builtin).unwrap()
+}
+
+async fn fetch(url: &str) -> Result<String, reqwest::Error> {
+    http::get_text(url).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compile_uses_custom_rule_with_required_groups() {
+        let custom = r"^fight (?P<stage>\S+) x(?P<times>\d+)".to_string();
+        let regex = compile("fight", Some(custom.clone()), FIGHT, &["stage", "times"]);
+        assert_eq!(regex.as_str(), custom);
+    }
+
+    #[test]
+    fn compile_falls_back_when_named_group_is_missing() {
+        let custom = r"^fight (?P<stage>\S+) (\d+) times".to_string();
+        let regex = compile("fight", Some(custom), FIGHT, &["stage", "times"]);
+        assert_eq!(regex.as_str(), FIGHT);
+    }
+
+    #[test]
+    fn compile_falls_back_when_rule_is_invalid() {
+        let regex = compile("error", Some("(ERROR".to_string()), ERROR, &[]);
+        assert_eq!(regex.as_str(), ERROR);
+    }
+
+    #[test]
+    fn pack_overrides_only_given_rules() {
+        let pack =
+            parse_pack(r#"{"version": "2026.10", "sanity": "耗费(?P<sanity>\\d+)理智"}"#).unwrap();
+        let rules = ParserRules::from_pack(pack);
+        assert_eq!(rules.version, "2026.10");
+        assert_eq!(rules.sanity.as_str(), r"耗费(?P<sanity>\d+)理智");
+        assert_eq!(rules.task.as_str(), TASK);
+    }
+}
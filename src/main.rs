@@ -1,162 +1,1104 @@
-mod server3;
+mod adb;
+mod cli;
+mod crash;
+mod data_dirs;
+mod diagnose;
+mod doctor;
+mod emulator;
+mod exit_code;
+mod failures;
+mod hooks;
+mod http;
+mod journald;
+mod maa_log;
+mod maa_output;
+mod maa_rules;
+mod maintenance;
+mod notifier;
+mod paths;
+mod process;
+mod progress;
+mod prune;
+mod resource_limits;
+mod run_dir;
+mod run_limit;
+mod run_lock;
+mod skip_dates;
+mod system_load;
+mod user_idle;
 
-use log;
-use server3::sc_send;
+use chrono::Local;
+use clap::Parser;
+use cli::{Cli, Commands};
+use crash::LogTee;
+use data_dirs::DataDirs;
+use emulator::{ComposeEnv, Container, Emulator, EmulatorError, Libvirt, Waydroid};
+use exit_code::ExitCode;
+use journald::Journald;
+use maa_log::{FailureReport, LogTail};
+use maa_output::MaaProgress;
+use maa_rules::ParserRules;
+use notifier::NotifyEvent;
+use paths::PathResolver;
+use process::{Cancellation, WaitResult};
+use progress::{Progress, Stage};
+use resource_limits::ResourceLimits;
+use run_dir::RunDir;
+use run_lock::RunLock;
+use skip_dates::SkipDate;
+use std::collections::HashSet;
 use std::env;
 use std::error::Error;
-use std::path::PathBuf;
-use std::process::{Command, exit};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tracing::Level;
+use system_load::LoadLimits;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::{self, writer::BoxMakeWriter};
+use tracing_subscriber::prelude::*;
+use user_idle::UserIdle;
+
+// 官服的游戏包名
+const DEFAULT_GAME_PACKAGE: &str = "com.hypergryph.arknights";
+// 状态目录下远程解析规则包的缓存
+const RULES_CACHE: &str = "parser_rules.json";
+// 覆盖任意配置项的环境变量前缀
+const ENV_OVERRIDE_PREFIX: &str = "EASY_MAA_";
+// 默认保留最近多少次运行的工作目录
+const DEFAULT_RUN_DIR_KEEP: usize = 30;
+// 正在运行的MAA所在的systemd单元，MAA结束后删除
+const UNIT_RECORD: &str = "maa_unit";
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    // 生成补全脚本和man page不需要加载配置
+    match cli.command {
+        Some(Commands::Completions { shell }) => {
+            cli::print_completions(shell);
+            return Ok(());
+        }
+        Some(Commands::Man) => {
+            cli::print_man()?;
+            return Ok(());
+        }
+        _ => {}
+    }
     // 只有注册 subscriber 后， 才能在控制台上看到日志输出
-    tracing_subscriber::fmt()
-        .with_max_level(Level::INFO) // 仅INFO、WARN、ERROR Level的日志会被打印
+    // 开启 --progress-json 时stdout只输出进度事件，日志改为输出到stderr
+    let console = if cli.progress_json {
+        BoxMakeWriter::new(|| LogTee::new(io::stderr()))
+    } else {
+        BoxMakeWriter::new(|| LogTee::new(io::stdout()))
+    };
+    // 开启 --journald 时同时写入journald，不带颜色、时间与级别（journald自己记录时间与优先级）
+    // 本次运行的ID，用于journald检索与运行工作目录的目录名
+    let run_id = format!(
+        "{}-{}",
+        Local::now().format("%Y%m%d-%H%M%S"),
+        std::process::id()
+    );
+    let journald = cli
+        .journald
+        .then(|| Journald::new(run_id.clone()))
+        .filter(Journald::connected);
+    let journald_enabled = journald.is_some();
+    tracing_subscriber::registry()
+        .with(LevelFilter::INFO) // 仅INFO、WARN、ERROR Level的日志会被打印
+        .with(fmt::layer().with_writer(console))
+        .with(journald.map(|journald| {
+            fmt::layer()
+                .with_ansi(false)
+                .without_time()
+                .with_level(false)
+                .with_writer(journald)
+        }))
         .init();
+    if journald_enabled {
+        log::info!("日志同时写入journald，RUN_ID={}", run_id);
+    }
+    let progress = Progress::new(cli.progress_json);
     // 从 .env 文件加载环境变量。
     // 如果 .env 文件找不到、不可读或无效，则加载失败。
-    load_env();
-    let maa_bin = env::var("MAA_BIN").expect("请在.env文件里设置MAA的二进制路径");
-    let container_name = env::var("CONTAINER_NAME").expect("请在.env文件里设置容器名");
-    let adb_target = env::var("ADB_TARGET").expect("请在.env文件里设置adb路径");
-    let maa_task_config =
-        env::var("MAA_TASK_CONFIG").expect("请在.env文件里设置MAA任务配置文件路径");
-    let user_name = env::var("USER_NAME").expect("请在.env文件里设置安装MAA的用户名");
-    let user_home = format!("/home/{}", user_name);
-    let maa_lib_dir = format!("{}/.local/share/maa/lib", user_home); // 你确认的库目录
-    let maa_state_dir = format!("{}/.local/state", user_home);
-    let maa_data_dir = format!("{}/.local/share", user_home);
-    let maa_config_dir = format!("{}/.config", user_home);
-    match sc_send("archMAA".to_string(), "MAA服务准备启动".to_string()).await {
-        Ok(_ret) => tracing::info!("Server3酱消息推送成功"),
-        Err(_e) => tracing::error!("Server3酱消息推送失败"),
-    }
-
-    // 这样可以执行命令
-    // let child = Command::new("pwd").output().expect("failed to execute process");
-    let podman = String::from_utf8(
-        Command::new("podman")
-            .arg("ps")
-            .arg("-a")
-            .output()
-            .expect("podman command failed to start")
-            .stdout,
+    let config_dir = load_env();
+    let user_name = required_env("USER_NAME", "请在.env文件里设置安装MAA的用户名");
+    let user_home = paths::user_home(&user_name);
+    let data_dirs = DataDirs::new(&user_name, &user_home);
+    // 只读取数据目录或只操作podman的子命令不需要MAA与模拟器的配置
+    if let Some(Commands::Paths) = cli.command {
+        for (name, dir) in data_dirs.all() {
+            println!("{}\t{}", name, dir.display());
+        }
+        return Ok(());
+    }
+    if let Some(Commands::Prune { containers, yes }) = cli.command {
+        let keep = env::var("CONTAINER_NAME").ok();
+        if !prune::run(keep.as_deref(), containers, yes) {
+            ExitCode::CommandFailed.exit();
+        }
+        return Ok(());
+    }
+    if let Some(Commands::Failures) = cli.command {
+        failures::print_clusters(data_dirs.state());
+        return Ok(());
+    }
+    if let Some(Commands::Artifacts { run_id }) = &cli.command {
+        if !run_dir::print_artifacts(&data_dirs.runs(), run_id.as_deref()) {
+            ExitCode::CommandFailed.exit();
+        }
+        return Ok(());
+    }
+    // 配置中的路径支持 ~、~user、环境变量与相对于配置文件目录的相对路径
+    let resolver = PathResolver::new(user_home.clone(), config_dir);
+    hooks::set_resolver(resolver.clone());
+    let maa_bin = resolve_config_path(&resolver, "MAA_BIN", "请在.env文件里设置MAA的二进制路径");
+    // libvirt后端未设置时从虚拟机的网络信息推导
+    let adb_target = env::var("ADB_TARGET").ok().filter(|v| !v.is_empty());
+    // 多个任务文件用逗号分隔，按顺序各执行一次 maa run
+    let maa_task_configs = resolve_task_configs(&resolver);
+    // MAA自己的日志文件，MAA失败时从中分析失败原因
+    let maa_logfile = match env::var("MAA_LOGFILE") {
+        Ok(raw) if !raw.is_empty() => resolver.resolve(&raw),
+        _ => maa_log::default_path(&user_home),
+    };
+    // MAA进程的nice、ionice与CPU配额
+    let resource_limits = ResourceLimits::new(
+        optional_env("MAA_NICE").as_deref(),
+        optional_env("MAA_IONICE").as_deref(),
+        optional_env("MAA_CPU_QUOTA").as_deref(),
+        optional_env("MAA_SYSTEMD_SCOPE").as_deref(),
+        &user_name,
     )
-    .expect("command not found");
+    .unwrap_or_else(|e| {
+        log::error!("{}", e);
+        ExitCode::Config.exit();
+    });
+    // MAA输出解析规则包，http(s)地址原样保留，其它按路径解析
+    let parser_rules = env::var("MAA_PARSER_RULES")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .map(|raw| {
+            if raw.starts_with("http://") || raw.starts_with("https://") {
+                raw
+            } else {
+                resolver.resolve(&raw).display().to_string()
+            }
+        });
+    // MAA_LOGFILE_TAIL=true 时在运行期间跟踪该文件，补充stdout里缺少的细节
+    let tail_maa_logfile = env::var("MAA_LOGFILE_TAIL").is_ok_and(|v| v == "true");
+    // 模拟器后端，未设置时配置了 COMPOSE_FILE 则用compose启动/关闭整套环境，否则使用单个容器
+    let backend = env::var("EMULATOR_BACKEND")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| {
+            if env::var("COMPOSE_FILE").is_ok_and(|v| !v.is_empty()) {
+                "compose".to_string()
+            } else {
+                "container".to_string()
+            }
+        });
+    let emulator: Box<dyn Emulator> = match backend.as_str() {
+        "container" => Box::new(Container::new(required_env(
+            "CONTAINER_NAME",
+            "请在.env文件里设置容器名",
+        ))),
+        "compose" => Box::new(ComposeEnv::new(resolve_config_path(
+            &resolver,
+            "COMPOSE_FILE",
+            "请在.env文件里设置compose文件路径",
+        ))),
+        "waydroid" => Box::new(Waydroid::new(user_name.clone())),
+        "libvirt" => Box::new(Libvirt::new(
+            required_env("LIBVIRT_DOMAIN", "请在.env文件里设置虚拟机名称"),
+            env::var("LIBVIRT_URI").ok().filter(|v| !v.is_empty()),
+            env_parse("LIBVIRT_ADB_PORT"),
+        )),
+        other => {
+            log::error!(
+                "EMULATOR_BACKEND配置有误: 不支持{}，可选值为container、compose、waydroid、libvirt",
+                other
+            );
+            ExitCode::Config.exit();
+        }
+    };
+    if adb_target.is_none() && backend != "libvirt" {
+        log::error!("请在.env文件里设置adb路径");
+        ExitCode::Config.exit();
+    }
+    if let Err(e) = data_dirs.ensure() {
+        log::warn!("数据目录初始化失败: {}", e);
+    }
+    let state_dir = data_dirs.state();
+    // panic时生成崩溃报告，并提示上次运行留下的报告
+    crash::install_panic_hook(data_dirs.crash_reports());
+    crash::report_pending(&data_dirs.crash_reports());
+
+    // 预先检查用到的外部命令是否存在，避免运行到一半才发现路径写错
+    let missing_programs: Vec<String> = ["adb"]
+        .into_iter()
+        .chain(emulator.programs())
+        .chain(resource_limits.programs())
+        .map(PathBuf::from)
+        .chain([maa_bin.clone()])
+        .filter(|program| paths::find_executable(program).is_none())
+        .map(|program| program.display().to_string())
+        .collect();
+    if !missing_programs.is_empty() {
+        log::error!(
+            "找不到以下命令或没有执行权限: {}",
+            missing_programs.join(", ")
+        );
+    }
 
-    // 首先确保容器是存在的
-    match podman.find(container_name.as_str()) {
-        Some(_podman) => {
-            log::info!("已找到运行Arknights的容器");
+    if let Some(Commands::Doctor) = cli.command {
+        let passed = doctor::run(
+            env::var("CONTAINER_NAME").ok().as_deref(),
+            adb_target.as_deref(),
+        )
+        .await;
+        if !passed || !missing_programs.is_empty() {
+            ExitCode::CommandFailed.exit();
         }
-        None => {
+        return Ok(());
+    }
+
+    if !missing_programs.is_empty() {
+        ExitCode::Config.exit();
+    }
+
+    if let Some(Commands::SyncTime) = cli.command {
+        let target = match adb_target.clone() {
+            Some(target) => Some(target),
+            None => emulator.adb_target().await,
+        };
+        let Some(target) = target else {
+            log::error!("无法获取{}的adb地址", emulator.label());
+            ExitCode::CommandFailed.exit();
+        };
+        if !adb::sync_time(&target).await {
             log::error!(
-                "请检查容器是否存在以及.env配置是否正确[提示:你是否使用sudo权限运行该工具?]"
+                "同步设备{}的时间失败，请确认模拟器正在运行且adb具有root权限",
+                target
             );
-            exit(1);
+            ExitCode::CommandFailed.exit();
         }
+        log::info!("已将设备{}的时间同步为宿主机时间", target);
+        return Ok(());
     }
 
-    // 运行容器
-    let podman = String::from_utf8(
-        Command::new("podman")
-            .arg("start")
-            .arg(container_name.as_str())
-            .output()
-            .expect("podman command failed to start")
-            .stdout,
-    )
-    .expect("command not found");
-    if !podman.is_empty() {
-        log::info!("容器已启动");
-    }
-
-    // 清理adb缓存
-    Command::new("adb")
-        .arg("kill-server")
-        .output()
-        .expect("adb command failed to start");
-
-    tokio::time::sleep(Duration::from_secs(1)).await;
-    Command::new("adb")
-        .arg("start-server")
-        .output()
-        .expect("adb command failed to start");
-    log::info!("adb已重启");
-
-    log::info!("等待5秒钟模拟器开机");
-    tokio::time::sleep(Duration::from_secs(5)).await;
-    // 连接模拟器设备
-    let adb = String::from_utf8(
-        Command::new("adb")
-            .arg("connect")
-            .arg(adb_target.as_str())
-            .output()
-            .expect("adb command failed to start")
-            .stdout,
+    // 同一时间只允许运行一个MAA任务
+    let run_lock = match RunLock::acquire(state_dir) {
+        Ok(Ok(lock)) => lock,
+        Ok(Err(pid)) => {
+            log::error!("已有MAA任务正在运行(PID {})，本次运行已拒绝", pid);
+            ExitCode::AlreadyRunning.exit();
+        }
+        Err(e) => {
+            log::error!(
+                "创建运行锁失败: {}，请检查状态目录{}的权限",
+                e,
+                state_dir.display()
+            );
+            ExitCode::Config.exit();
+        }
+    };
+
+    // 处于游戏维护时段时跳过本次运行
+    let maintenance_windows =
+        maintenance::parse_windows(&env::var("MAINTENANCE_WINDOWS").unwrap_or_default())
+            .unwrap_or_else(|e| {
+                log::error!("MAINTENANCE_WINDOWS配置有误: {}", e);
+                ExitCode::Config.exit();
+            });
+    let now = Local::now().naive_local();
+    if let Some(window) = maintenance_windows.iter().find(|w| w.contains(now)) {
+        log::warn!("当前处于游戏维护时段({})，跳过本次运行", window);
+        notifier::notify(NotifyEvent::MaintenanceSkipped, None).await;
+        return Ok(());
+    }
+
+    // 排除日期（SKIP_DATES与SKIP_DATES_ICS日历）当天不运行，手动运行可用--force跳过检查
+    if let Some(skip) = today_skipped(&resolver).await {
+        if !cli.force {
+            log::warn!(
+                "今日在排除日期内({})，跳过本次运行[提示:手动运行可加--force强制执行]",
+                skip
+            );
+            notifier::notify(NotifyEvent::DateSkipped, Some(&skip.to_string())).await;
+            return Ok(());
+        }
+        log::warn!("今日在排除日期内({})，--force强制运行", skip);
+    }
+
+    // 每日最大运行次数，未设置或为0时不限制
+    let daily_max_runs = env_parse::<u32>("MAA_DAILY_MAX_RUNS").filter(|max| *max > 0);
+    if let Some(max) = daily_max_runs {
+        let runs = run_limit::today_runs(state_dir);
+        if runs >= max {
+            if !cli.force {
+                log::warn!(
+                    "今日已运行{}次，达到每日上限{}次，跳过本次运行[提示:手动运行可加--force强制执行]",
+                    runs,
+                    max
+                );
+                return Ok(());
+            }
+            log::warn!(
+                "今日已运行{}次，达到每日上限{}次，--force强制运行",
+                runs,
+                max
+            );
+        }
+    }
+    // 宿主机负载或IO压力过高（如正在备份）时延迟启动
+    let load_limits = LoadLimits {
+        load: env_parse("LOAD_MAX"),
+        io_pressure: env_parse("IO_PRESSURE_MAX"),
+        delay: Duration::from_secs(env_parse("LOAD_DELAY_MINUTES").unwrap_or(5) * 60),
+        retries: env_parse("LOAD_DELAY_RETRIES").unwrap_or(3),
+    };
+    load_limits.wait_until_idle().await;
+    // USER_IDLE_MINUTES 设置后，用户正在使用电脑时推迟运行
+    if let Some(minutes) = env_parse::<u64>("USER_IDLE_MINUTES").filter(|m| *m > 0)
+        && !cli.force
+    {
+        let user_idle = UserIdle {
+            min_idle: Duration::from_secs(minutes * 60),
+            delay: Duration::from_secs(env_parse("USER_IDLE_DELAY_MINUTES").unwrap_or(10) * 60),
+            retries: env_parse("USER_IDLE_RETRIES").unwrap_or(6),
+        };
+        if !user_idle.wait_until_idle(&user_name).await {
+            return Ok(());
+        }
+    }
+
+    // 本次运行的工作目录，RUN_DIR_KEEP=0 时不创建
+    let run_dir_keep = env_parse::<usize>("RUN_DIR_KEEP").unwrap_or(DEFAULT_RUN_DIR_KEEP);
+    let run_dir = (run_dir_keep > 0)
+        .then(|| {
+            RunDir::create(
+                &data_dirs.runs(),
+                &run_id,
+                run_dir_keep,
+                paths::root_owner(&user_name),
+            )
+            .inspect_err(|e| log::warn!("创建运行工作目录失败: {}", e))
+            .ok()
+        })
+        .flatten();
+    if let Some(run_dir) = &run_dir {
+        hooks::set_run_dir(run_dir.id(), run_dir.path());
+    }
+    if notifier::milestone_enabled(NotifyEvent::Started) {
+        notifier::notify(NotifyEvent::Started, None).await;
+    }
+
+    // 从这里开始模拟器可能已被启动，Ctrl-C/SIGTERM不再直接结束进程，而是取消本次运行并照常关闭模拟器
+    let cancellation = Cancellation::listen().unwrap_or_else(|e| {
+        log::error!("注册信号处理失败: {}", e);
+        ExitCode::Config.exit();
+    });
+
+    progress.start(Stage::CheckContainer);
+    if let Err(e) = emulator.check().await {
+        log::error!("{}", e);
+        progress.fail(Stage::CheckContainer, &e.summary);
+        ExitCode::ContainerMissing.exit();
+    }
+    progress.finish(Stage::CheckContainer);
+
+    // 启动模拟器，已经在运行时直接跳过
+    progress.start(Stage::StartContainer);
+    log::info!("准备启动{}", emulator.label());
+    if let Err(e) = emulator.start().await {
+        log::error!("{}", e);
+        log_diagnosis(&e.log);
+        let category = diagnose::diagnose_container_error(&e.log)
+            .map(|diagnosis| diagnosis.kind.to_string())
+            .unwrap_or_else(|| "模拟器启动失败".to_string());
+        record_failure(
+            state_dir,
+            ExitCode::ContainerMissing,
+            &category,
+            e.log.lines().last(),
+        );
+        progress.fail(Stage::StartContainer, &e.summary);
+        notify_emulator_failure(&e).await;
+        ExitCode::ContainerMissing.exit();
+    }
+    progress.finish(Stage::StartContainer);
+
+    // 清理adb缓存，adb无法启动时按adb连接失败处理，照常关闭模拟器并推送通知
+    progress.start(Stage::RestartAdb);
+    let adb_restarted = match adb::restart_server().await {
+        Ok(()) => {
+            log::info!("adb已重启");
+            progress.finish(Stage::RestartAdb);
+            true
+        }
+        Err(e) => {
+            log::error!("重启adb失败: {}", e);
+            progress.fail(Stage::RestartAdb, "重启adb失败");
+            false
+        }
+    };
+
+    let boot_wait = env_parse("EMULATOR_BOOT_WAIT").unwrap_or(5);
+    log::info!("等待{}秒钟模拟器开机", boot_wait);
+    tokio::select! {
+        _ = tokio::time::sleep(Duration::from_secs(boot_wait)) => {}
+        _ = cancellation.cancelled() => {}
+    }
+    // 连接模拟器设备，设备就绪后才运行MAA
+    progress.start(Stage::ConnectAdb);
+    // 未配置ADB_TARGET时每次重试都重新向后端取地址，虚拟机刚开机时可能还没有分配到地址
+    let connected = if adb_restarted && !cancellation.is_cancelled() {
+        let (configured, backend) = (adb_target.as_deref(), emulator.as_ref());
+        adb::connect_with_retry(
+            move || async move {
+                match configured {
+                    Some(target) => Some(target.to_string()),
+                    None => backend.adb_target().await,
+                }
+            },
+            env_parse("ADB_CONNECT_RETRIES").unwrap_or(3),
+            Duration::from_secs(env_parse("ADB_RETRY_INTERVAL").unwrap_or(5)),
+            &cancellation,
+        )
+        .await
+    } else {
+        None
+    };
+    if adb_target.is_none()
+        && let Some(target) = &connected
+    {
+        log::info!("{}的adb地址: {}", emulator.label(), target);
+    }
+    let adb_ready = connected.is_some();
+    let adb_target = connected.or(adb_target).unwrap_or_default();
+    let game_ready = if cancellation.is_cancelled() {
+        false
+    } else if !adb_ready {
+        if adb_target.is_empty() && adb_restarted {
+            log::error!("无法获取{}的adb地址，跳过MAA任务", emulator.label());
+        } else {
+            log::error!("设备{}未就绪，跳过MAA任务", adb_target);
+        }
+        progress.fail(Stage::ConnectAdb, "adb连接模拟器失败");
+        false
+    } else {
+        progress.finish(Stage::ConnectAdb);
+        wait_for_game(&progress, &adb_target).await
+    };
+    let mut detail = None;
+    let outcome = if cancellation.is_cancelled() {
+        RunOutcome::Cancelled
+    } else if !adb_ready {
+        RunOutcome::AdbFailed
+    } else if !game_ready {
+        RunOutcome::GameNotReady
+    } else {
+        // 模拟器与游戏都就绪、开始执行MAA时才计入每日运行次数，模拟器或adb失败的运行可以被重试
+        if let Err(e) = run_limit::record_run(state_dir) {
+            log::warn!("记录今日运行次数失败: {}", e);
+        }
+        let maa = MaaConfig {
+            bin: maa_bin,
+            user_name,
+            user_home,
+            logfile: maa_logfile.clone(),
+            tail_logfile: tail_maa_logfile,
+            parser_rules,
+            limits: resource_limits,
+            state_dir: state_dir.to_path_buf(),
+            cancellation: cancellation.clone(),
+        };
+        let (outcome, maa_detail) =
+            run_task_configs(&cli, &progress, &maa, &maa_task_configs).await;
+        detail = maa_detail;
+        outcome
+    };
+
+    progress.start(Stage::StopContainer);
+    match emulator.stop().await {
+        Ok(()) => progress.finish(Stage::StopContainer),
+        Err(e) => {
+            log::error!("{}", e);
+            progress.fail(Stage::StopContainer, &e.summary);
+            notify_emulator_failure(&e).await;
+        }
+    }
+
+    let event = match outcome {
+        RunOutcome::Succeeded => NotifyEvent::Finished,
+        RunOutcome::AdbFailed => NotifyEvent::AdbFailed,
+        RunOutcome::GameNotReady => NotifyEvent::GameNotReady,
+        RunOutcome::MaaFailed => NotifyEvent::Failed,
+        RunOutcome::Cancelled => NotifyEvent::Cancelled,
+    };
+    // 本地记录失败指纹，供 `easy_maa failures` 统计；MAA的失败已按任务文件分别记录
+    let category = match outcome {
+        RunOutcome::AdbFailed => Some("adb连接失败"),
+        RunOutcome::GameNotReady => Some("游戏未就绪"),
+        _ => None,
+    };
+    if let (Some(code), Some(category)) = (outcome.exit_code(), category) {
+        record_failure(state_dir, code, category, None);
+    }
+    notifier::notify(event, detail.as_deref()).await;
+    if let Some(run_dir) = &run_dir {
+        run_dir.finish(detail.as_deref());
+    }
+
+    drop(run_lock);
+    match outcome.exit_code() {
+        Some(code) => code.exit(),
+        None => Ok(()),
+    }
+}
+
+/// 一次运行的结果，决定推送的通知与退出码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunOutcome {
+    Succeeded,
+    AdbFailed,
+    GameNotReady,
+    MaaFailed,
+    Cancelled,
+}
+
+impl RunOutcome {
+    /// 对应的退出码，成功时为None
+    fn exit_code(&self) -> Option<ExitCode> {
+        match self {
+            RunOutcome::Succeeded => None,
+            RunOutcome::AdbFailed => Some(ExitCode::AdbFailed),
+            RunOutcome::GameNotReady => Some(ExitCode::GameNotReady),
+            RunOutcome::MaaFailed => Some(ExitCode::MaaFailed),
+            RunOutcome::Cancelled => Some(ExitCode::Cancelled),
+        }
+    }
+}
+
+/// GAME_READY_CHECK=true 时检查游戏进程是否就绪，GAME_AUTO_LAUNCH=true 时自动拉起游戏
+async fn wait_for_game(progress: &Progress, adb_target: &str) -> bool {
+    if !env::var("GAME_READY_CHECK").is_ok_and(|v| v == "true") {
+        return true;
+    }
+    let package = env::var("GAME_PACKAGE")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_GAME_PACKAGE.to_string());
+    progress.start(Stage::LaunchGame);
+    let ready = adb::wait_for_game(
+        adb_target,
+        &package,
+        env::var("GAME_AUTO_LAUNCH").is_ok_and(|v| v == "true"),
+        Duration::from_secs(env_parse("GAME_READY_TIMEOUT").unwrap_or(60)),
     )
-    .expect("adb command failed to start");
-    log::info!("{:?}", adb);
+    .await;
+    if ready {
+        log::info!("游戏{}已就绪", package);
+        progress.finish(Stage::LaunchGame);
+    } else {
+        log::error!("游戏{}未能就绪，跳过MAA任务", package);
+        progress.fail(Stage::LaunchGame, "游戏未能就绪");
+    }
+    ready
+}
+
+/// 按顺序执行各个任务文件，汇总为一个运行结果与附在通知里的总结
+///
+/// 每个失败的任务文件分别取证并记录失败指纹；解析规则只加载一次，所有任务文件共用。
+/// MAA_STOP_ON_ERROR=false 时某个任务文件失败后继续执行后面的文件，取消时总是立即停止
+async fn run_task_configs(
+    cli: &Cli,
+    progress: &Progress,
+    config: &MaaConfig,
+    task_configs: &[PathBuf],
+) -> (RunOutcome, Option<String>) {
+    let output = MaaOutput {
+        rules: Arc::new(
+            ParserRules::load(
+                config.parser_rules.as_deref(),
+                &config.state_dir.join(RULES_CACHE),
+            )
+            .await,
+        ),
+        // OUTPUT_ALERT_KEYWORDS 为逗号分隔的关键字，MAA输出中出现时立即推送提醒
+        keywords: Arc::new(
+            env::var("OUTPUT_ALERT_KEYWORDS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|keyword| !keyword.is_empty())
+                .map(String::from)
+                .collect(),
+        ),
+    };
+    if let [task_config] = task_configs {
+        let run = run_maa(cli, progress, config, &output, task_config).await;
+        record_maa_failure(config, &run);
+        return (run.outcome, run.detail());
+    }
+    let stop_on_error = !env::var("MAA_STOP_ON_ERROR").is_ok_and(|v| v == "false");
+    let mut outcome = RunOutcome::Succeeded;
+    let mut sections = Vec::new();
+    for (index, task_config) in task_configs.iter().enumerate() {
+        // 两个任务文件之间收到的取消
+        if config.cancellation.is_cancelled() {
+            outcome = RunOutcome::Cancelled;
+            skip_task_configs(&mut sections, &task_configs[index..]);
+            break;
+        }
+        log::info!(
+            "执行任务文件{}（{}/{}）",
+            task_config.display(),
+            index + 1,
+            task_configs.len()
+        );
+        let run = run_maa(cli, progress, config, &output, task_config).await;
+        record_maa_failure(config, &run);
+        let file_outcome = run.outcome;
+        let status = match file_outcome {
+            RunOutcome::Succeeded => "成功",
+            RunOutcome::Cancelled => "已取消",
+            _ => "失败",
+        };
+        log::info!("任务文件{}执行{}", task_config.display(), status);
+        sections.push(match run.detail() {
+            Some(summary) => format!("【{}】{}\n{}", task_name(task_config), status, summary),
+            None => format!("【{}】{}", task_name(task_config), status),
+        });
+        if file_outcome == RunOutcome::Succeeded {
+            continue;
+        }
+        outcome = file_outcome;
+        if file_outcome == RunOutcome::Cancelled || stop_on_error {
+            skip_task_configs(&mut sections, &task_configs[index + 1..]);
+            break;
+        }
+    }
+    (outcome, Some(sections.join("\n\n")))
+}
+
+/// MAA失败时记录失败指纹，类别取自日志取证的诊断结果
+fn record_maa_failure(config: &MaaConfig, run: &MaaRun) {
+    if run.outcome != RunOutcome::MaaFailed {
+        return;
+    }
+    let category = run
+        .failure
+        .as_ref()
+        .and_then(|report| report.diagnosis.as_ref())
+        .map(|diagnosis| diagnosis.kind.to_string())
+        .unwrap_or_else(|| "MAA运行失败".to_string());
+    let key_line = run
+        .failure
+        .as_ref()
+        .and_then(|report| report.errors.last())
+        .map(String::as_str);
+    record_failure(&config.state_dir, ExitCode::MaaFailed, &category, key_line);
+}
+
+/// 把不再执行的任务文件记入总结
+fn skip_task_configs(sections: &mut Vec<String>, skipped: &[PathBuf]) {
+    if skipped.is_empty() {
+        return;
+    }
+    log::warn!("跳过剩余的{}个任务文件", skipped.len());
+    sections.extend(
+        skipped
+            .iter()
+            .map(|task_config| format!("【{}】未执行", task_name(task_config))),
+    );
+}
+
+/// 通知里显示的任务文件名
+fn task_name(task_config: &Path) -> String {
+    task_config
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| task_config.display().to_string())
+}
+
+/// 运行MAA所需的配置
+struct MaaConfig {
+    bin: PathBuf,
+    user_name: String,
+    user_home: PathBuf,
+    /// MAA日志文件
+    logfile: PathBuf,
+    /// 运行期间是否跟踪MAA日志文件
+    tail_logfile: bool,
+    /// MAA输出解析规则包，本地路径或http(s)地址
+    parser_rules: Option<String>,
+    limits: ResourceLimits,
+    /// 状态目录，存放远程规则包的缓存与MAA所在的单元名
+    state_dir: PathBuf,
+    /// 运行被取消时终止MAA
+    cancellation: Cancellation,
+}
 
-    // 运行MAA
+/// 所有任务文件共用的MAA输出解析规则与提醒关键字
+struct MaaOutput {
+    rules: Arc<ParserRules>,
+    keywords: Arc<Vec<String>>,
+}
+
+/// 一个任务文件的运行结果
+struct MaaRun {
+    outcome: RunOutcome,
+    /// 从MAA输出中解析出的总结
+    summary: Option<String>,
+    /// MAA失败时从日志中取证的结果
+    failure: Option<FailureReport>,
+}
+
+impl MaaRun {
+    /// 附在通知里的内容：运行总结，失败时再附上失败原因
+    fn detail(&self) -> Option<String> {
+        let failure = self.failure.as_ref().map(FailureReport::to_string);
+        match (&self.summary, failure) {
+            (Some(summary), Some(failure)) => Some(format!("{}\n\n{}", summary, failure)),
+            (summary, failure) => summary.clone().or(failure),
+        }
+    }
+}
 
-    let mut child = Command::new(maa_bin.as_str())
+/// 以原始用户的身份环境运行MAA任务，等待其结束，返回运行结果、解析出的总结与失败取证
+async fn run_maa(
+    cli: &Cli,
+    progress: &Progress,
+    config: &MaaConfig,
+    output: &MaaOutput,
+    task_config: &Path,
+) -> MaaRun {
+    progress.start(Stage::RunMaa);
+    // 只从本次写入的日志中取证
+    let log_offset = maa_log::log_len(&config.logfile);
+    let user_home = &config.user_home;
+    // 在scope单元中运行时把单元名写入状态目录，本工具崩溃后也能找到并停止MAA
+    let unit = format!("easy-maa-{}", Local::now().format("%Y%m%d-%H%M%S"));
+    let unit_record = config.state_dir.join(UNIT_RECORD);
+    if config.limits.in_scope() {
+        log::info!(
+            "MAA将在单元{}中运行，可通过 {} 停止",
+            unit,
+            config.limits.stop_command(&unit)
+        );
+        if let Err(e) = std::fs::write(&unit_record, format!("{}.scope\n", unit)) {
+            log::warn!("记录MAA单元名失败: {}", e);
+        }
+    }
+    let spawned = config
+        .limits
+        .command(&config.bin, &unit)
+        // 输出经由本工具转发，便于解析进度
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
         .arg("run")
-        .arg(maa_task_config.as_str())
+        .arg(task_config)
         // 设置库路径（只影响子进程）
-        .env("LD_LIBRARY_PATH", maa_lib_dir.as_str())
+        .env("LD_LIBRARY_PATH", user_home.join(".local/share/maa/lib")) // 你确认的库目录
         // 让 maa 看到原始用户的 HOME/USER/XDG_*，避免使用 /root
-        .env("HOME", &user_home)
-        .env("USER", &user_name)
-        .env("XDG_STATE_HOME", maa_state_dir)
-        .env("XDG_DATA_HOME", maa_data_dir)
-        .env("XDG_CONFIG_HOME", maa_config_dir)
+        .env("HOME", user_home)
+        .env("USER", &config.user_name)
+        .env("XDG_STATE_HOME", user_home.join(".local/state"))
+        .env("XDG_DATA_HOME", user_home.join(".local/share"))
+        .env("XDG_CONFIG_HOME", user_home.join(".config"))
         // 如果 maa 需要工作目录（资源），可设置 current_dir：
-        .current_dir(format!("{}/.local/share/maa", user_home))
-        .spawn()
-        .expect("maa task command failed to start");
-
-    let status = child.wait()?;
-    println!("Child exited with: {}", status);
-    log::info!("MAA任务执行完毕");
-
-    let podman = String::from_utf8(
-        Command::new("podman")
-            .arg("stop")
-            .arg(container_name.as_str())
-            .output()
-            .expect("podman command failed to start")
-            .stdout,
-    );
-    log::info!("{:?}", podman);
+        .current_dir(user_home.join(".local/share/maa"))
+        .spawn();
+    let tail = config
+        .tail_logfile
+        .then(|| LogTail::spawn(config.logfile.clone()));
+    let (alerts, alert_sender) = maa_output::spawn_alerts();
+    let state = Arc::new(Mutex::new(MaaProgress::default()));
+    let mut readers = Vec::new();
+    // Ctrl-C或SIGTERM会终止MAA，之后照常关闭容器
+    let result = match spawned {
+        Ok(mut child) => {
+            if let Some(stdout) = child.stdout.take() {
+                // stdout留给进度事件，MAA的输出转到stderr
+                let echo: Box<dyn io::Write + Send> = if cli.progress_json {
+                    Box::new(io::stderr())
+                } else {
+                    Box::new(io::stdout())
+                };
+                readers.push(maa_output::spawn_reader(
+                    stdout,
+                    echo,
+                    output.rules.clone(),
+                    output.keywords.clone(),
+                    state.clone(),
+                    *progress,
+                    alerts.clone(),
+                ));
+            }
+            if let Some(stderr) = child.stderr.take() {
+                readers.push(maa_output::spawn_reader(
+                    stderr,
+                    io::stderr(),
+                    output.rules.clone(),
+                    output.keywords.clone(),
+                    state.clone(),
+                    *progress,
+                    alerts.clone(),
+                ));
+            }
+            process::wait_cancellable(&mut child, &config.cancellation).await
+        }
+        Err(e) => Err(e),
+    };
+    for reader in readers {
+        let _ = reader.await;
+    }
+    // 读取输出的任务结束后发送端全部关闭，等待提醒推送完
+    drop(alerts);
+    let _ = alert_sender.await;
+    if let Some(tail) = tail {
+        tail.stop().await;
+    }
+    if config.limits.in_scope() {
+        let _ = std::fs::remove_file(&unit_record);
+    }
+    let summary = {
+        let state = state.lock().unwrap();
+        if state.lines > 0 {
+            log::info!(
+                "MAA输出共{}行，{}行命中解析规则(规则版本: {})",
+                state.lines,
+                state.matched_lines,
+                output.rules.version
+            );
+        }
+        state.summary()
+    };
+    let outcome = match result {
+        Ok(WaitResult::Exited(status)) => {
+            log::info!("Child exited with: {}", status);
+            if status.success() {
+                RunOutcome::Succeeded
+            } else {
+                RunOutcome::MaaFailed
+            }
+        }
+        Ok(WaitResult::Cancelled) => RunOutcome::Cancelled,
+        Err(e) => {
+            log::error!("MAA启动失败: {}", e);
+            RunOutcome::MaaFailed
+        }
+    };
+    match outcome {
+        RunOutcome::Succeeded => {
+            log::info!("MAA任务执行完毕");
+            progress.finish(Stage::RunMaa);
+        }
+        RunOutcome::Cancelled => {
+            log::warn!("MAA任务已取消");
+            progress.fail(Stage::RunMaa, "MAA任务已取消");
+        }
+        _ => {
+            log::error!("MAA任务执行失败");
+            progress.fail(Stage::RunMaa, "MAA任务执行失败");
+        }
+    }
+    let failure = (outcome == RunOutcome::MaaFailed)
+        .then(|| maa_log::failure_report(&config.logfile, log_offset))
+        .flatten();
+    MaaRun {
+        outcome,
+        summary,
+        failure,
+    }
+}
 
-    match sc_send("archMAA".to_string(), "MAA运行完毕".to_string()).await {
-        Ok(_ret) => tracing::info!("Server3酱消息推送成功"),
-        Err(_e) => tracing::error!("Server3酱消息推送失败"),
+/// 读取可选的数值配置，未设置或无法解析时返回None
+fn env_parse<T: FromStr>(key: &str) -> Option<T> {
+    env::var(key).ok().and_then(|v| v.trim().parse().ok())
+}
+
+/// 识别容器报错里的SELinux/AppArmor等已知问题并打印修复建议
+fn log_diagnosis(stderr: &str) {
+    if let Some(diagnosis) = diagnose::diagnose_container_error(stderr) {
+        log::error!("{}", diagnosis);
     }
+}
 
-    log::info!("已关闭podman容器");
+/// compose失败时把聚合后的输出推送出去，只保留末尾若干行避免消息过长
+async fn notify_emulator_failure(err: &EmulatorError) {
+    if !err.notify {
+        return;
+    }
+    let lines: Vec<&str> = err.log.lines().collect();
+    let tail = lines[lines.len().saturating_sub(30)..].join("\n");
+    let detail = format!("{}\n\n```\n{}\n```", err.summary, tail);
+    notifier::notify(NotifyEvent::ComposeFailed, Some(&detail)).await;
+}
 
-    Ok(())
+/// 今天命中的排除日期；SKIP_DATES_ICS 可以是本地文件或http(s)订阅地址，读取失败时只打印警告
+async fn today_skipped(resolver: &PathResolver) -> Option<SkipDate> {
+    let mut skips =
+        skip_dates::parse(&env::var("SKIP_DATES").unwrap_or_default()).unwrap_or_else(|e| {
+            log::error!("SKIP_DATES配置有误: {}", e);
+            ExitCode::Config.exit();
+        });
+    if let Some(source) = env::var("SKIP_DATES_ICS").ok().filter(|v| !v.is_empty()) {
+        let content = if source.starts_with("http://") || source.starts_with("https://") {
+            http::get_text(&source).await.map_err(|e| e.to_string())
+        } else {
+            std::fs::read_to_string(resolver.resolve(&source)).map_err(|e| e.to_string())
+        };
+        match content {
+            Ok(content) => skips.extend(skip_dates::parse_ics(&content)),
+            Err(e) => log::warn!("读取排除日期日历{}失败: {}", source, e),
+        }
+    }
+    let today = Local::now().date_naive();
+    skips.into_iter().find(|skip| skip.contains(today))
 }
 
-fn load_env() {
-    // 1. 尝试从固定用户配置目录加载：~/.config/easy_maa/.env
-        let config_path = PathBuf::from("/home/cn059/.config/easy_maa/.env");
+/// 记录失败指纹，写入出错只打印警告
+fn record_failure(state_dir: &Path, code: ExitCode, category: &str, key_line: Option<&str>) {
+    if let Err(e) = failures::record(state_dir, code as i32, category, key_line) {
+        log::warn!("写入失败记录出错: {}", e);
+    }
+}
 
-        log::info!("Trying to load env from: {:?}", config_path);
+/// 读取可选的字符串配置，未设置或为空时返回None
+fn optional_env(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|v| !v.trim().is_empty())
+}
+
+/// 读取必填配置项，未设置时以配置错误退出
+fn required_env(key: &str, missing_msg: &str) -> String {
+    match env::var(key) {
+        Ok(value) => value,
+        Err(_) => {
+            log::error!("{}", missing_msg);
+            ExitCode::Config.exit();
+        }
+    }
+}
 
-        if config_path.exists() {
-            dotenvy::from_path(config_path).expect("Failed to load .env from ~/.config/easy_maa/.env");
-            return;
+/// 读取路径类配置项并解析，路径不存在时以配置错误退出
+fn resolve_config_path(resolver: &PathResolver, key: &str, missing_msg: &str) -> PathBuf {
+    let raw = required_env(key, missing_msg);
+    // 不带路径的程序名（如 maa）交给 PATH 查找
+    if key == "MAA_BIN" && !raw.contains('/') && !raw.starts_with(['~', '$']) {
+        return PathBuf::from(raw);
+    }
+    match resolver.resolve_existing(&raw) {
+        Ok(path) => path,
+        Err(e) => {
+            log::error!("{}配置有误: {}", key, e);
+            ExitCode::Config.exit();
         }
+    }
+}
+
+/// MAA_TASK_CONFIG 中逗号分隔的任务文件，逐个解析为路径
+fn resolve_task_configs(resolver: &PathResolver) -> Vec<PathBuf> {
+    let raw = required_env("MAA_TASK_CONFIG", "请在.env文件里设置MAA任务配置文件路径");
+    let task_configs: Vec<PathBuf> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| match resolver.resolve_existing(item) {
+            Ok(path) => path,
+            Err(e) => {
+                log::error!("MAA_TASK_CONFIG配置有误: {}", e);
+                ExitCode::Config.exit();
+            }
+        })
+        .collect();
+    if task_configs.is_empty() {
+        log::error!("请在.env文件里设置MAA任务配置文件路径");
+        ExitCode::Config.exit();
+    }
+    task_configs
+}
+
+/// 加载 .env 配置，返回配置文件所在目录，用于解析配置中的相对路径
+fn load_env() -> PathBuf {
+    let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    // 1. 尝试从用户配置目录加载：~/.config/easy_maa/.env（使用sudo运行时为原始用户的目录）
+    let config_path = PathResolver::new(paths::invoking_user_home(), cwd.clone())
+        .resolve("$XDG_CONFIG_HOME/easy_maa/.env");
+
+    log::info!("Trying to load env from: {:?}", config_path);
 
+    // 已存在的进程环境变量优先于.env文件，dotenvy不会覆盖它们
+    let process_keys: HashSet<String> = env::vars_os()
+        .filter_map(|(key, _)| key.into_string().ok())
+        .collect();
+
+    if config_path.exists() {
+        let file_keys = env_file_keys(&config_path);
+        if let Err(e) = dotenvy::from_path(&config_path) {
+            log::error!("Failed to load .env from {:?}: {}", config_path, e);
+            ExitCode::Config.exit();
+        }
+        apply_env_overrides(&file_keys, &process_keys);
+        return config_path.parent().map(PathBuf::from).unwrap_or(cwd);
+    }
 
     // 2. 否则回退到当前目录（仅开发模式）
     #[cfg(debug_assertions)]
     {
-        dotenvy::dotenv().ok();
+        if let Ok(path) = dotenvy::dotenv() {
+            apply_env_overrides(&env_file_keys(&path), &process_keys);
+            return path.parent().map(PathBuf::from).unwrap_or(cwd);
+        }
+    }
+    apply_env_overrides(&[], &process_keys);
+    cwd
+}
+
+/// .env文件中出现的配置项名称
+fn env_file_keys(path: &Path) -> Vec<String> {
+    dotenvy::from_path_iter(path)
+        .map(|iter| iter.flatten().map(|(key, _)| key).collect())
+        .unwrap_or_default()
+}
+
+/// 配置分层：.env文件 < 同名进程环境变量 < EASY_MAA_<KEY>
+///
+/// 任意配置项都可以用 EASY_MAA_ 前缀的环境变量覆盖（如 EASY_MAA_ADB_TARGET），
+/// 便于在systemd单元或临时调试时不改.env；日志只打印生效来源，不打印取值
+fn apply_env_overrides(file_keys: &[String], process_keys: &HashSet<String>) {
+    for key in file_keys {
+        if process_keys.contains(key) {
+            log::info!("配置项{}使用进程环境变量，忽略.env中的值", key);
+        }
+    }
+    let overrides: Vec<(String, String)> = env::vars_os()
+        .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+        .filter_map(|(key, value)| {
+            let key = key.strip_prefix(ENV_OVERRIDE_PREFIX)?;
+            (!key.is_empty()).then(|| (key.to_string(), value))
+        })
+        .collect();
+    for (key, value) in overrides {
+        let layer = if process_keys.contains(&key) {
+            "覆盖进程环境变量"
+        } else if file_keys.contains(&key) {
+            "覆盖.env中的值"
+        } else {
+            "新增"
+        };
+        log::info!(
+            "配置项{}使用{}{}（{}）",
+            key,
+            ENV_OVERRIDE_PREFIX,
+            key,
+            layer
+        );
+        // SAFETY: 启动阶段读取配置时还没有其他线程访问环境变量
+        unsafe { env::set_var(&key, value) };
     }
 }
@@ -0,0 +1,122 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy)]
+enum Day {
+    /// 每周固定的维护日
+    Weekly(Weekday),
+    /// 临时维护的具体日期
+    Date(NaiveDate),
+}
+
+/// 游戏维护时段，如 `Thu 16:00-21:00` 或 `2025-05-01 10:00-15:00`
+#[derive(Debug, Clone, Copy)]
+pub struct MaintenanceWindow {
+    day: Day,
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl MaintenanceWindow {
+    pub fn contains(&self, now: NaiveDateTime) -> bool {
+        let day_matches = match self.day {
+            Day::Weekly(weekday) => now.weekday() == weekday,
+            Day::Date(date) => now.date() == date,
+        };
+        day_matches && self.start <= now.time() && now.time() < self.end
+    }
+}
+
+impl fmt::Display for MaintenanceWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.day {
+            Day::Weekly(weekday) => write!(f, "每周{}", weekday)?,
+            Day::Date(date) => write!(f, "{}", date)?,
+        }
+        write!(
+            f,
+            " {}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+/// 解析 MAINTENANCE_WINDOWS 配置，多个时段用逗号分隔
+pub fn parse_windows(raw: &str) -> Result<Vec<MaintenanceWindow>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(parse_window)
+        .collect()
+}
+
+fn parse_window(item: &str) -> Result<MaintenanceWindow, String> {
+    let invalid = || format!("无法解析维护时段: {}", item);
+    let (day, range) = item.split_once(' ').ok_or_else(invalid)?;
+    let day = match NaiveDate::parse_from_str(day, "%Y-%m-%d") {
+        Ok(date) => Day::Date(date),
+        Err(_) => Day::Weekly(day.parse::<Weekday>().map_err(|_| invalid())?),
+    };
+    let (start, end) = range.trim().split_once('-').ok_or_else(invalid)?;
+    let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").map_err(|_| invalid())?;
+    let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").map_err(|_| invalid())?;
+    if start >= end {
+        return Err(invalid());
+    }
+    Ok(MaintenanceWindow { day, start, end })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(raw: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn parses_weekly_and_dated_windows() {
+        let windows = parse_windows("Thu 16:00-21:00, 2025-05-01 10:00-15:00,").unwrap();
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].to_string(), "每周Thu 16:00-21:00");
+        assert_eq!(windows[1].to_string(), "2025-05-01 10:00-15:00");
+    }
+
+    #[test]
+    fn weekly_window_excludes_its_end() {
+        let window = parse_windows("Thu 16:00-21:00").unwrap()[0];
+        // 2025-05-08是周四
+        assert!(window.contains(at("2025-05-08 16:00")));
+        assert!(window.contains(at("2025-05-08 20:59")));
+        assert!(!window.contains(at("2025-05-08 21:00")));
+        assert!(!window.contains(at("2025-05-08 15:59")));
+        assert!(!window.contains(at("2025-05-09 17:00")));
+    }
+
+    #[test]
+    fn dated_window_only_matches_that_day() {
+        let window = parse_windows("2025-05-01 10:00-15:00").unwrap()[0];
+        assert!(window.contains(at("2025-05-01 12:00")));
+        assert!(!window.contains(at("2025-05-08 12:00")));
+    }
+
+    #[test]
+    fn rejects_invalid_windows() {
+        for raw in [
+            "Thu 21:00-16:00",
+            "Thu 16:00-16:00",
+            "Funday 10:00-11:00",
+            "Thu 16:00",
+            "Thu",
+            "Thu 25:00-26:00",
+        ] {
+            assert!(parse_windows(raw).is_err(), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn empty_config_has_no_windows() {
+        assert!(parse_windows("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,48 @@
+use super::{Notifier, NotifyEvent, SendFuture, post_json};
+use crate::http;
+use serde_json::json;
+use std::env;
+
+// 未配置 BARK_URL 时使用的官方服务器
+const DEFAULT_SERVER: &str = "https://api.day.app";
+
+/// Bark（iOS推送），需要配置 BARK_KEY，自建服务器配置 BARK_URL
+pub struct Bark {
+    server: String,
+    key: String,
+}
+
+impl Bark {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            server: env::var("BARK_URL")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| DEFAULT_SERVER.to_string()),
+            key: env::var("BARK_KEY").ok().filter(|v| !v.is_empty())?,
+        })
+    }
+}
+
+impl Notifier for Bark {
+    fn name(&self) -> &'static str {
+        "bark"
+    }
+
+    fn label(&self) -> &'static str {
+        "Bark"
+    }
+
+    fn send<'a>(&'a self, title: &'a str, message: &'a str, _event: NotifyEvent) -> SendFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}/push", self.server.trim_end_matches('/'));
+            let body = json!({
+                "device_key": self.key,
+                "title": title,
+                "body": message,
+                "group": "easy_maa",
+            });
+            post_json(http::client().post(&url), body).await
+        })
+    }
+}
@@ -0,0 +1,57 @@
+use super::{Notifier, NotifyEvent, SendFuture, post_json};
+use crate::http;
+use serde_json::json;
+use std::env;
+
+/// 自托管的Gotify服务，需要配置 GOTIFY_URL 与 GOTIFY_TOKEN（应用token）
+pub struct Gotify {
+    server: String,
+    token: String,
+}
+
+impl Gotify {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            server: env::var("GOTIFY_URL").ok().filter(|v| !v.is_empty())?,
+            token: env::var("GOTIFY_TOKEN").ok().filter(|v| !v.is_empty())?,
+        })
+    }
+}
+
+/// Gotify消息优先级，可通过 GOTIFY_PRIORITY_<事件> 覆盖，失败类事件默认更高
+fn priority(event: NotifyEvent) -> u8 {
+    let default = match event {
+        NotifyEvent::Started => 2,
+        _ if event.is_urgent() => 8,
+        _ => 5,
+    };
+    env::var(format!("GOTIFY_PRIORITY_{}", event.key()))
+        .ok()
+        .and_then(|priority| priority.parse().ok())
+        .unwrap_or(default)
+}
+
+impl Notifier for Gotify {
+    fn name(&self) -> &'static str {
+        "gotify"
+    }
+
+    fn label(&self) -> &'static str {
+        "Gotify"
+    }
+
+    fn send<'a>(&'a self, title: &'a str, message: &'a str, event: NotifyEvent) -> SendFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}/message", self.server.trim_end_matches('/'));
+            let request = http::client()
+                .post(&url)
+                .header("X-Gotify-Key", &self.token);
+            let body = json!({
+                "title": title,
+                "message": message,
+                "priority": priority(event),
+            });
+            post_json(request, body).await
+        })
+    }
+}
@@ -0,0 +1,241 @@
+mod bark;
+mod gotify;
+mod ntfy;
+mod server3;
+mod telegram;
+mod webhook;
+
+use crate::hooks;
+use reqwest::RequestBuilder;
+use reqwest::header::CONTENT_TYPE;
+use std::env;
+use std::error::Error;
+use std::future::Future;
+use std::pin::Pin;
+
+// 未配置 NOTIFY_TITLE 时使用的推送标题
+const DEFAULT_TITLE: &str = "archMAA";
+
+/// 需要推送通知的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyEvent {
+    /// MAA服务准备启动
+    Started,
+    /// MAA运行完毕
+    Finished,
+    /// 作战任务完成
+    FightDone,
+    /// 基建任务完成
+    InfrastDone,
+    /// MAA运行失败
+    Failed,
+    /// MAA运行中被取消
+    Cancelled,
+    /// adb无法连接模拟器
+    AdbFailed,
+    /// 游戏进程未能就绪
+    GameNotReady,
+    /// 游戏维护时段内跳过运行
+    MaintenanceSkipped,
+    /// 当天在排除日期内跳过运行
+    DateSkipped,
+    /// compose环境启动或关闭失败
+    ComposeFailed,
+    /// MAA输出中出现订阅的关键字
+    KeywordAlert,
+}
+
+impl NotifyEvent {
+    /// 事件文案对应的配置项后缀，如 Started 对应 NOTIFY_TEXT_STARTED
+    pub fn key(&self) -> &'static str {
+        match self {
+            NotifyEvent::Started => "STARTED",
+            NotifyEvent::Finished => "FINISHED",
+            NotifyEvent::FightDone => "FIGHT_DONE",
+            NotifyEvent::InfrastDone => "INFRAST_DONE",
+            NotifyEvent::Failed => "FAILED",
+            NotifyEvent::Cancelled => "CANCELLED",
+            NotifyEvent::AdbFailed => "ADB_FAILED",
+            NotifyEvent::GameNotReady => "GAME_NOT_READY",
+            NotifyEvent::MaintenanceSkipped => "MAINTENANCE_SKIPPED",
+            NotifyEvent::DateSkipped => "DATE_SKIPPED",
+            NotifyEvent::ComposeFailed => "COMPOSE_FAILED",
+            NotifyEvent::KeywordAlert => "KEYWORD_ALERT",
+        }
+    }
+
+    /// 失败与关键字提醒等需要尽快处理的事件，支持优先级的渠道会提高优先级
+    pub fn is_urgent(&self) -> bool {
+        matches!(
+            self,
+            NotifyEvent::Failed
+                | NotifyEvent::AdbFailed
+                | NotifyEvent::GameNotReady
+                | NotifyEvent::ComposeFailed
+                | NotifyEvent::KeywordAlert
+        )
+    }
+
+    fn default_text(&self) -> &'static str {
+        match self {
+            NotifyEvent::Started => "MAA服务准备启动",
+            NotifyEvent::Finished => "MAA运行完毕",
+            NotifyEvent::FightDone => "作战任务已完成",
+            NotifyEvent::InfrastDone => "基建任务已完成",
+            NotifyEvent::Failed => "MAA运行失败",
+            NotifyEvent::Cancelled => "MAA任务已取消",
+            NotifyEvent::AdbFailed => "adb连接模拟器失败",
+            NotifyEvent::GameNotReady => "游戏未能启动",
+            NotifyEvent::MaintenanceSkipped => "因维护跳过本次任务",
+            NotifyEvent::DateSkipped => "今日为排除日期，跳过本次任务",
+            NotifyEvent::ComposeFailed => "compose环境操作失败",
+            NotifyEvent::KeywordAlert => "MAA输出中出现关注的关键字",
+        }
+    }
+
+    /// 事件文案，可通过 NOTIFY_TEXT_<事件> 覆盖默认值
+    pub fn text(&self) -> String {
+        env::var(format!("NOTIFY_TEXT_{}", self.key()))
+            .ok()
+            .filter(|text| !text.is_empty())
+            .unwrap_or_else(|| self.default_text().to_string())
+    }
+}
+
+/// 是否推送运行中的里程碑通知（开始、作战完成、基建完成），结束时的通知总会推送
+///
+/// NOTIFY_MILESTONES 为逗号分隔的事件名，如 `started,fight_done,infrast_done`，未设置时只推送开始；
+/// 设置为空字符串则只在结束时推送一条
+pub fn milestone_enabled(event: NotifyEvent) -> bool {
+    match env::var("NOTIFY_MILESTONES") {
+        Ok(raw) => raw
+            .split(',')
+            .any(|name| name.trim().eq_ignore_ascii_case(event.key())),
+        Err(_) => event == NotifyEvent::Started,
+    }
+}
+
+/// 推送标题，多台机器部署时可通过 NOTIFY_TITLE 区分推送来源
+pub fn title() -> String {
+    env::var("NOTIFY_TITLE")
+        .ok()
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| DEFAULT_TITLE.to_string())
+}
+
+pub type SendResult = Result<(), Box<dyn Error>>;
+pub type SendFuture<'a> = Pin<Box<dyn Future<Output = SendResult> + Send + 'a>>;
+
+/// 通知渠道，每个渠道从环境变量读取自己的配置
+pub trait Notifier: Send + Sync {
+    /// 渠道名称，用于 NOTIFY_CHANNELS 与 NOTIFY_EVENTS_<渠道> 配置
+    fn name(&self) -> &'static str;
+
+    /// 日志里显示的渠道名称
+    fn label(&self) -> &'static str;
+
+    fn send<'a>(&'a self, title: &'a str, message: &'a str, event: NotifyEvent) -> SendFuture<'a>;
+}
+
+// 支持的全部渠道，未配置 NOTIFY_CHANNELS 时按这个顺序启用已配置的渠道
+const CHANNELS: &[&str] = &[
+    "serverchan",
+    "gotify",
+    "webhook",
+    "telegram",
+    "ntfy",
+    "bark",
+];
+
+/// 根据渠道名称创建渠道，渠道所需的配置缺失时返回None
+fn build(name: &str) -> Option<Box<dyn Notifier>> {
+    fn boxed<N: Notifier + 'static>(notifier: N) -> Box<dyn Notifier> {
+        Box::new(notifier)
+    }
+    match name {
+        "serverchan" => server3::ServerChan::from_env().map(boxed),
+        "gotify" => gotify::Gotify::from_env().map(boxed),
+        "webhook" => webhook::Webhook::from_env().map(boxed),
+        "telegram" => telegram::Telegram::from_env().map(boxed),
+        "ntfy" => ntfy::Ntfy::from_env().map(boxed),
+        "bark" => bark::Bark::from_env().map(boxed),
+        _ => None,
+    }
+}
+
+/// 启用的渠道，按 NOTIFY_CHANNELS（如 `gotify,serverchan`）的顺序；未配置时启用全部已配置的渠道
+fn channels() -> Vec<Box<dyn Notifier>> {
+    match env::var("NOTIFY_CHANNELS") {
+        Ok(list) if !list.trim().is_empty() => list
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .filter_map(|name| {
+                let notifier = build(name);
+                if notifier.is_none() {
+                    if CHANNELS.contains(&name) {
+                        log::warn!("通知渠道{}缺少配置，已跳过", name);
+                    } else {
+                        log::warn!("未知的通知渠道: {}", name);
+                    }
+                }
+                notifier
+            })
+            .collect(),
+        _ => CHANNELS.iter().filter_map(|name| build(name)).collect(),
+    }
+}
+
+/// 渠道是否接收该事件，通过 NOTIFY_EVENTS_<渠道>（如 `NOTIFY_EVENTS_TELEGRAM="failed,finished"`）过滤，
+/// 未配置时接收全部事件
+fn accepts(notifier: &dyn Notifier, event: NotifyEvent) -> bool {
+    match env::var(format!("NOTIFY_EVENTS_{}", notifier.name().to_uppercase())) {
+        Ok(events) if !events.trim().is_empty() => events
+            .split(',')
+            .any(|key| key.trim().eq_ignore_ascii_case(event.key())),
+        _ => true,
+    }
+}
+
+/// 以JSON格式发送请求，非2xx响应视为失败
+///
+/// 错误里去掉请求地址：Telegram等渠道的地址中带有token，错误会写进日志
+async fn post_json(request: RequestBuilder, body: serde_json::Value) -> SendResult {
+    request
+        .header(CONTENT_TYPE, "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .map_err(|e| e.without_url())?;
+    Ok(())
+}
+
+/// 推送事件通知，`detail` 会追加在事件文案之后；各渠道的推送失败只记录日志
+///
+/// 推送前先执行该事件配置的钩子脚本（HOOK_<事件>）
+///
+/// NOTIFY_FALLBACK=true 时按渠道顺序推送，直到有一个渠道成功为止，否则推送到全部渠道
+pub async fn notify(event: NotifyEvent, detail: Option<&str>) {
+    hooks::run(event, detail).await;
+    let desp = match detail {
+        Some(detail) => format!("{}\n\n{}", event.text(), detail),
+        None => event.text(),
+    };
+    let title = title();
+    let fallback = env::var("NOTIFY_FALLBACK").is_ok_and(|v| v == "true");
+    for notifier in channels() {
+        if !accepts(notifier.as_ref(), event) {
+            continue;
+        }
+        match notifier.send(&title, &desp, event).await {
+            Ok(()) => {
+                tracing::info!("{}消息推送成功", notifier.label());
+                if fallback {
+                    break;
+                }
+            }
+            Err(e) => tracing::error!("{}消息推送失败: {}", notifier.label(), e),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use super::{Notifier, NotifyEvent, SendFuture, post_json};
+use crate::http;
+use serde_json::json;
+use std::env;
+
+// 未配置 NTFY_URL 时使用的公共服务器
+const DEFAULT_SERVER: &str = "https://ntfy.sh";
+
+/// ntfy，需要配置 NTFY_TOPIC，自建服务器配置 NTFY_URL，需要鉴权时配置 NTFY_TOKEN
+pub struct Ntfy {
+    server: String,
+    topic: String,
+    token: Option<String>,
+}
+
+impl Ntfy {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            server: env::var("NTFY_URL")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| DEFAULT_SERVER.to_string()),
+            topic: env::var("NTFY_TOPIC").ok().filter(|v| !v.is_empty())?,
+            token: env::var("NTFY_TOKEN").ok().filter(|v| !v.is_empty()),
+        })
+    }
+}
+
+impl Notifier for Ntfy {
+    fn name(&self) -> &'static str {
+        "ntfy"
+    }
+
+    fn label(&self) -> &'static str {
+        "ntfy"
+    }
+
+    fn send<'a>(&'a self, title: &'a str, message: &'a str, event: NotifyEvent) -> SendFuture<'a> {
+        Box::pin(async move {
+            // 标题可能含中文，使用JSON发布而不是Title请求头
+            let priority = if event.is_urgent() { 4 } else { 3 };
+            let mut request = http::client().post(self.server.trim_end_matches('/'));
+            if let Some(token) = &self.token {
+                request = request.bearer_auth(token);
+            }
+            let body = json!({
+                "topic": self.topic,
+                "title": title,
+                "message": message,
+                "priority": priority,
+            });
+            post_json(request, body).await
+        })
+    }
+}
@@ -0,0 +1,74 @@
+use super::{Notifier, NotifyEvent, SendFuture};
+use crate::http;
+use regex::Regex;
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use std::env;
+use std::error::Error;
+
+pub async fn sc_send(text: String, desp: String) -> Result<String, Box<dyn Error>> {
+    let key = env::var("SENDKEY")?;
+    let params = [("text", text), ("desp", desp)];
+    let post_data = serde_urlencoded::to_string(params)?;
+    // 使用正则表达式提取 key 中的数字部分
+    //Server酱3的key的前四个字符都是sctp
+    let url = {
+        let re = Regex::new(r"sctp(\d+)t")?;
+        if let Some(captures) = re.captures(&key) {
+            let num = &captures[1]; // 提取正则表达式捕获的数字部分
+            format!("https://{}.push.ft07.com/send/{}.send", num, key)
+        } else {
+            return Err("Invalid sendkey format for sctp".into());
+        }
+    };
+    let client = http::client();
+    // 地址中带有SENDKEY，错误里去掉地址再返回
+    let res = client
+        .post(&url)
+        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .header(CONTENT_LENGTH, post_data.len() as u64)
+        .body(post_data)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .map_err(|e| e.without_url())?;
+    let data = res.text().await.map_err(|e| e.without_url())?;
+    // 被拒绝或超出额度时HTTP状态仍为200，结果在返回的code里
+    let reply: serde_json::Value = serde_json::from_str(&data)?;
+    match reply["code"].as_i64() {
+        Some(0) => Ok(data),
+        _ => Err(format!(
+            "Server酱返回错误: {}",
+            reply["message"].as_str().unwrap_or(&data)
+        )
+        .into()),
+    }
+}
+
+/// Server酱3，需要配置 SENDKEY
+pub struct ServerChan;
+
+impl ServerChan {
+    pub fn from_env() -> Option<Self> {
+        env::var("SENDKEY")
+            .ok()
+            .filter(|key| !key.is_empty())
+            .map(|_| ServerChan)
+    }
+}
+
+impl Notifier for ServerChan {
+    fn name(&self) -> &'static str {
+        "serverchan"
+    }
+
+    fn label(&self) -> &'static str {
+        "Server3酱"
+    }
+
+    fn send<'a>(&'a self, title: &'a str, message: &'a str, _event: NotifyEvent) -> SendFuture<'a> {
+        Box::pin(async move {
+            sc_send(title.to_string(), message.to_string()).await?;
+            Ok(())
+        })
+    }
+}
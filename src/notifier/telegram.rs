@@ -0,0 +1,44 @@
+use super::{Notifier, NotifyEvent, SendFuture, post_json};
+use crate::http;
+use serde_json::json;
+use std::env;
+
+/// Telegram机器人，需要配置 TELEGRAM_BOT_TOKEN 与 TELEGRAM_CHAT_ID
+pub struct Telegram {
+    token: String,
+    chat_id: String,
+}
+
+impl Telegram {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            token: env::var("TELEGRAM_BOT_TOKEN")
+                .ok()
+                .filter(|v| !v.is_empty())?,
+            chat_id: env::var("TELEGRAM_CHAT_ID")
+                .ok()
+                .filter(|v| !v.is_empty())?,
+        })
+    }
+}
+
+impl Notifier for Telegram {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    fn label(&self) -> &'static str {
+        "Telegram"
+    }
+
+    fn send<'a>(&'a self, title: &'a str, message: &'a str, _event: NotifyEvent) -> SendFuture<'a> {
+        Box::pin(async move {
+            let url = format!("https://api.telegram.org/bot{}/sendMessage", self.token);
+            let body = json!({
+                "chat_id": self.chat_id,
+                "text": format!("{}\n\n{}", title, message),
+            });
+            post_json(http::client().post(&url), body).await
+        })
+    }
+}
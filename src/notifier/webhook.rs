@@ -0,0 +1,38 @@
+use super::{Notifier, NotifyEvent, SendFuture, post_json};
+use crate::http;
+use serde_json::json;
+use std::env;
+
+/// 通用webhook，以JSON POST到 WEBHOOK_URL：`{"title": ..., "message": ..., "event": "finished"}`
+pub struct Webhook {
+    url: String,
+}
+
+impl Webhook {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            url: env::var("WEBHOOK_URL").ok().filter(|v| !v.is_empty())?,
+        })
+    }
+}
+
+impl Notifier for Webhook {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn label(&self) -> &'static str {
+        "Webhook"
+    }
+
+    fn send<'a>(&'a self, title: &'a str, message: &'a str, event: NotifyEvent) -> SendFuture<'a> {
+        Box::pin(async move {
+            let body = json!({
+                "title": title,
+                "message": message,
+                "event": event.key().to_lowercase(),
+            });
+            post_json(http::client().post(&self.url), body).await
+        })
+    }
+}
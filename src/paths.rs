@@ -0,0 +1,143 @@
+use regex::{Captures, Regex};
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// 配置里的路径统一通过这里解析，支持:
+/// - `~`、`~/xxx`：安装MAA的用户的家目录
+/// - `~user/xxx`：指定用户的家目录
+/// - `$VAR`、`${VAR}`：环境变量，XDG目录变量未设置时按XDG规范回退到家目录下的默认值
+/// - 相对路径：相对于配置文件所在目录
+#[derive(Clone)]
+pub struct PathResolver {
+    home: PathBuf,
+    base_dir: PathBuf,
+}
+
+impl PathResolver {
+    pub fn new(home: PathBuf, base_dir: PathBuf) -> Self {
+        Self { home, base_dir }
+    }
+
+    pub fn resolve(&self, raw: &str) -> PathBuf {
+        let expanded = self.expand_vars(raw.trim());
+        let path = self.expand_tilde(&expanded);
+        if path.is_absolute() {
+            path
+        } else {
+            self.base_dir.join(path)
+        }
+    }
+
+    /// 解析路径并检查路径是否存在
+    pub fn resolve_existing(&self, raw: &str) -> Result<PathBuf, String> {
+        let path = self.resolve(raw);
+        if path.exists() {
+            Ok(path)
+        } else {
+            Err(format!("路径不存在: {} (解析为 {})", raw, path.display()))
+        }
+    }
+
+    fn expand_vars(&self, raw: &str) -> String {
+        let re = Regex::new(r"\$\{(\w+)\}|\$(\w+)").unwrap();
+        re.replace_all(raw, |caps: &Captures| {
+            let name = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+            match env::var(name) {
+                Ok(value) if !value.is_empty() => value,
+                _ => match self.xdg_default(name) {
+                    Some(dir) => dir.to_string_lossy().into_owned(),
+                    // 未知变量保留原样，方便在校验时看出是哪个变量没有设置
+                    None => caps[0].to_string(),
+                },
+            }
+        })
+        .into_owned()
+    }
+
+    fn expand_tilde(&self, raw: &str) -> PathBuf {
+        let Some(rest) = raw.strip_prefix('~') else {
+            return PathBuf::from(raw);
+        };
+        let (user, tail) = match rest.split_once('/') {
+            Some((user, tail)) => (user, tail),
+            None => (rest, ""),
+        };
+        let home = if user.is_empty() {
+            self.home.clone()
+        } else {
+            user_home(user)
+        };
+        home.join(tail)
+    }
+
+    fn xdg_default(&self, name: &str) -> Option<PathBuf> {
+        let dir = match name {
+            "XDG_CONFIG_HOME" => ".config",
+            "XDG_DATA_HOME" => ".local/share",
+            "XDG_STATE_HOME" => ".local/state",
+            "XDG_CACHE_HOME" => ".cache",
+            "HOME" => "",
+            _ => return None,
+        };
+        Some(self.home.join(dir))
+    }
+}
+
+/// 查找可执行文件：带路径时直接检查该文件，否则在PATH中查找，需要是有执行权限的普通文件
+pub fn find_executable(program: &Path) -> Option<PathBuf> {
+    let is_executable = |path: &Path| {
+        path.metadata()
+            .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    };
+    if program.components().count() > 1 {
+        return is_executable(program).then(|| program.to_path_buf());
+    }
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|path| is_executable(path))
+}
+
+/// 从 /etc/passwd 查找用户的记录，按 `:` 分隔为各字段
+fn passwd_entry(user: &str) -> Option<Vec<String>> {
+    let passwd = fs::read_to_string("/etc/passwd").ok()?;
+    passwd.lines().find_map(|line| {
+        let fields: Vec<String> = line.split(':').map(String::from).collect();
+        (fields.len() > 5 && fields[0] == user).then_some(fields)
+    })
+}
+
+/// 从 /etc/passwd 查找用户的家目录，找不到时回退到 /home/<user>
+pub fn user_home(user: &str) -> PathBuf {
+    passwd_entry(user)
+        .map(|fields| PathBuf::from(&fields[5]))
+        .unwrap_or_else(|| Path::new("/home").join(user))
+}
+
+/// 用户的uid与gid
+pub fn user_ids(user: &str) -> Option<(u32, u32)> {
+    let fields = passwd_entry(user)?;
+    Some((fields[2].parse().ok()?, fields[3].parse().ok()?))
+}
+
+/// 是否以root身份运行（通常是通过sudo）
+pub fn is_root() -> bool {
+    // SAFETY: geteuid没有副作用
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// 以root运行时创建的目录、启动的进程应交还给的用户（uid与gid），不是root时返回None
+pub fn root_owner(user: &str) -> Option<(u32, u32)> {
+    if is_root() { user_ids(user) } else { None }
+}
+
+/// 运行本工具的用户的家目录，使用sudo运行时取原始用户而不是root
+pub fn invoking_user_home() -> PathBuf {
+    match env::var("SUDO_USER") {
+        Ok(user) if !user.is_empty() => user_home(&user),
+        _ => env::var("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("/root")),
+    }
+}
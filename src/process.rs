@@ -0,0 +1,85 @@
+use crate::exit_code::ExitCode;
+use std::io;
+use std::process::ExitStatus;
+use std::time::Duration;
+use tokio::process::Child;
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::sync::watch;
+
+// 发送SIGTERM后等待子进程自行退出的时间，超时后发送SIGKILL
+const TERMINATE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub enum WaitResult {
+    Exited(ExitStatus),
+    /// 收到Ctrl-C或SIGTERM，子进程已被终止
+    Cancelled,
+}
+
+/// 运行期间的Ctrl-C/SIGTERM，整个运行只注册一次
+///
+/// 第一次收到信号时标记为取消：正在运行的MAA被终止、剩余的任务文件不再执行，之后照常关闭模拟器并推送通知；
+/// 再次收到信号时立即退出
+#[derive(Clone)]
+pub struct Cancellation {
+    cancelled: watch::Receiver<bool>,
+}
+
+impl Cancellation {
+    pub fn listen() -> io::Result<Self> {
+        let mut sigint = signal(SignalKind::interrupt())?;
+        let mut sigterm = signal(SignalKind::terminate())?;
+        let (sender, cancelled) = watch::channel(false);
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = sigint.recv() => log::warn!("收到Ctrl-C"),
+                    _ = sigterm.recv() => log::warn!("收到SIGTERM"),
+                }
+                if *sender.borrow() {
+                    log::warn!("再次收到终止信号，立即退出");
+                    ExitCode::Cancelled.exit();
+                }
+                log::warn!("正在取消本次运行，再次发送信号可立即退出");
+                let _ = sender.send(true);
+            }
+        });
+        Ok(Self { cancelled })
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow()
+    }
+
+    /// 等待取消
+    pub async fn cancelled(&self) {
+        let mut cancelled = self.cancelled.clone();
+        if cancelled.wait_for(|cancelled| *cancelled).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// 等待子进程结束，期间运行被取消时先SIGTERM再SIGKILL终止子进程
+pub async fn wait_cancellable(
+    child: &mut Child,
+    cancellation: &Cancellation,
+) -> io::Result<WaitResult> {
+    tokio::select! {
+        status = child.wait() => return status.map(WaitResult::Exited),
+        _ = cancellation.cancelled() => log::warn!("正在终止MAA"),
+    }
+    if let Some(pid) = child.id() {
+        // SAFETY: 只向自己启动且尚未回收的子进程发送信号
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGTERM);
+        }
+    }
+    if tokio::time::timeout(TERMINATE_TIMEOUT, child.wait())
+        .await
+        .is_err()
+    {
+        log::warn!("MAA在{}秒内未退出，强制结束", TERMINATE_TIMEOUT.as_secs());
+        child.kill().await?;
+    }
+    Ok(WaitResult::Cancelled)
+}
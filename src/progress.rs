@@ -0,0 +1,97 @@
+use crate::maa_output::MaaProgress;
+use chrono::Local;
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// 进度事件格式的版本，事件删除或改变已有字段、阶段、状态的含义时加一；新增字段或枚举值不改版本，
+/// 读取方应忽略不认识的字段与值
+const PROTOCOL_VERSION: u32 = 1;
+
+/// 运行过程中的各个阶段
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    CheckContainer,
+    StartContainer,
+    RestartAdb,
+    ConnectAdb,
+    LaunchGame,
+    RunMaa,
+    StopContainer,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageStatus {
+    Started,
+    Finished,
+    Failed,
+    /// MAA运行中的进度更新
+    Progress,
+}
+
+#[derive(Serialize)]
+struct ProgressEvent<'a> {
+    version: u32,
+    stage: Stage,
+    status: StageStatus,
+    timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    maa: Option<&'a MaaProgress>,
+}
+
+/// `--progress-json` 开启时，把每个阶段的开始/结束以NDJSON（一行一个JSON）输出到stdout
+#[derive(Clone, Copy)]
+pub struct Progress {
+    enabled: bool,
+}
+
+impl Progress {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    pub fn start(&self, stage: Stage) {
+        self.emit(stage, StageStatus::Started, None, None);
+    }
+
+    pub fn finish(&self, stage: Stage) {
+        self.emit(stage, StageStatus::Finished, None, None);
+    }
+
+    pub fn fail(&self, stage: Stage, message: &str) {
+        self.emit(stage, StageStatus::Failed, Some(message), None);
+    }
+
+    /// MAA的任务、作战次数等解析结果
+    pub fn maa_progress(&self, maa: &MaaProgress) {
+        self.emit(Stage::RunMaa, StageStatus::Progress, None, Some(maa));
+    }
+
+    fn emit(
+        &self,
+        stage: Stage,
+        status: StageStatus,
+        message: Option<&str>,
+        maa: Option<&MaaProgress>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let event = ProgressEvent {
+            version: PROTOCOL_VERSION,
+            stage,
+            status,
+            timestamp: Local::now().to_rfc3339(),
+            message,
+            maa,
+        };
+        if let Ok(line) = serde_json::to_string(&event) {
+            let mut stdout = io::stdout().lock();
+            let _ = writeln!(stdout, "{}", line);
+            let _ = stdout.flush();
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::process::Command;
+
+/// 运行podman命令并返回stdout，失败时打印stderr
+fn podman(args: &[&str]) -> Option<String> {
+    match Command::new("podman").args(args).output() {
+        Ok(output) if output.status.success() => {
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        }
+        Ok(output) => {
+            log::error!(
+                "podman {}执行失败: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            None
+        }
+        Err(e) => {
+            log::error!("podman启动失败: {}", e);
+            None
+        }
+    }
+}
+
+fn lines(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// `easy_maa prune`：清理悬空镜像，`containers` 时还清理已停止的容器，运行游戏的容器始终保留
+///
+/// 默认只列出将被清理的资源，`yes` 时才真正执行
+pub fn run(keep_container: Option<&str>, containers: bool, yes: bool) -> bool {
+    if let Some(df) = podman(&["system", "df"]) {
+        println!("{}\n", df);
+    }
+    let Some(images) = podman(&[
+        "images",
+        "--filter",
+        "dangling=true",
+        "--format",
+        "{{.ID}} {{.Size}}",
+    ]) else {
+        return false;
+    };
+    let images = lines(&images);
+    let stopped = if containers {
+        let Some(output) = podman(&[
+            "ps",
+            "-a",
+            "--filter",
+            "status=exited",
+            "--filter",
+            "status=created",
+            "--format",
+            "{{.Names}}",
+        ]) else {
+            return false;
+        };
+        lines(&output)
+            .into_iter()
+            .filter(|name| Some(name.as_str()) != keep_container)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    if images.is_empty() && stopped.is_empty() {
+        println!("没有需要清理的资源");
+        return true;
+    }
+    println!("悬空镜像{}个:", images.len());
+    for image in &images {
+        println!("  {}", image);
+    }
+    if containers {
+        println!("已停止的容器{}个:", stopped.len());
+        for name in &stopped {
+            println!("  {}", name);
+        }
+    }
+    if !yes {
+        println!("\n以上为预览，确认后加上 --yes 执行清理");
+        return true;
+    }
+
+    let mut ok = true;
+    if !stopped.is_empty() {
+        let mut args = vec!["rm"];
+        args.extend(stopped.iter().map(String::as_str));
+        ok &= podman(&args).is_some();
+    }
+    if !images.is_empty() {
+        match podman(&["image", "prune", "-f"]) {
+            Some(output) => println!("已删除镜像:\n{}", output),
+            None => ok = false,
+        }
+    }
+    if let Some(df) = podman(&["system", "df"]) {
+        println!("\n清理后:\n{}", df);
+    }
+    ok
+}
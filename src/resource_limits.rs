@@ -0,0 +1,223 @@
+use crate::paths;
+use std::ffi::OsString;
+use std::path::Path;
+use tokio::process::Command;
+
+/// ionice的调度类别，格式为 `idle`、`best-effort[:0-7]` 或 `realtime[:0-7]`
+#[derive(Debug, Clone, Copy)]
+enum IoClass {
+    Idle,
+    BestEffort(Option<u8>),
+    Realtime(Option<u8>),
+}
+
+impl IoClass {
+    fn parse(raw: &str) -> Result<Self, String> {
+        let invalid = || format!("无法解析IO调度类别: {}", raw);
+        let (class, level) = match raw.split_once(':') {
+            Some((class, level)) => (
+                class,
+                Some(level.trim().parse::<u8>().map_err(|_| invalid())?),
+            ),
+            None => (raw, None),
+        };
+        if level.is_some_and(|level| level > 7) {
+            return Err(invalid());
+        }
+        match (class.trim(), level) {
+            ("idle", None) => Ok(IoClass::Idle),
+            ("best-effort", level) => Ok(IoClass::BestEffort(level)),
+            ("realtime", level) => Ok(IoClass::Realtime(level)),
+            _ => Err(invalid()),
+        }
+    }
+
+    fn args(&self) -> Vec<String> {
+        let (class, level) = match self {
+            IoClass::Idle => ("3", None),
+            IoClass::BestEffort(level) => ("2", *level),
+            IoClass::Realtime(level) => ("1", *level),
+        };
+        let mut args = vec!["-c".to_string(), class.to_string()];
+        if let Some(level) = level {
+            args.extend(["-n".to_string(), level.to_string()]);
+        }
+        args
+    }
+}
+
+/// systemd-run瞬态scope单元归属的管理器，对应 MAA_SYSTEMD_SCOPE=system/user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScopeMode {
+    System,
+    User,
+}
+
+/// MAA进程的资源限制，通过nice、ionice与 `systemd-run --scope -p CPUQuota=` 包装MAA命令
+///
+/// MAA的OCR会占满CPU，限制后不影响宿主机的前台使用。在scope单元中运行时，
+/// 本工具崩溃后仍可以通过单元名找到并停止MAA
+pub struct ResourceLimits {
+    /// 对应 MAA_NICE，-20到19
+    nice: Option<i32>,
+    /// 对应 MAA_IONICE
+    ionice: Option<IoClass>,
+    /// 对应 MAA_CPU_QUOTA，如 200% 表示最多占用两个核
+    cpu_quota: Option<String>,
+    /// 在systemd-run瞬态scope单元中运行，设置了CPU配额时默认为system
+    scope: Option<ScopeMode>,
+    /// `--user` 模式下以root运行时连接该用户的systemd管理器
+    user_name: String,
+}
+
+impl ResourceLimits {
+    pub fn new(
+        nice: Option<&str>,
+        ionice: Option<&str>,
+        cpu_quota: Option<&str>,
+        scope: Option<&str>,
+        user_name: &str,
+    ) -> Result<Self, String> {
+        let nice = nice
+            .map(|raw| {
+                raw.trim()
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|nice| (-20..=19).contains(nice))
+                    .ok_or_else(|| format!("MAA_NICE应为-20到19的整数: {}", raw))
+            })
+            .transpose()?;
+        let ionice = ionice.map(IoClass::parse).transpose()?;
+        let cpu_quota = cpu_quota
+            .map(|raw| {
+                let raw = raw.trim();
+                raw.strip_suffix('%')
+                    .and_then(|percent| percent.parse::<u32>().ok())
+                    .filter(|percent| *percent > 0)
+                    .map(|_| raw.to_string())
+                    .ok_or_else(|| format!("MAA_CPU_QUOTA应为百分比，如200%: {}", raw))
+            })
+            .transpose()?;
+        let scope = match scope.map(str::trim) {
+            None => cpu_quota.as_ref().map(|_| ScopeMode::System),
+            Some("system") => Some(ScopeMode::System),
+            Some("user") => Some(ScopeMode::User),
+            Some(other) => {
+                return Err(format!("MAA_SYSTEMD_SCOPE应为system或user: {}", other));
+            }
+        };
+        Ok(Self {
+            nice,
+            ionice,
+            cpu_quota,
+            scope,
+            user_name: user_name.to_string(),
+        })
+    }
+
+    /// 包装用到的外部命令，启动前随其它命令一起检查
+    pub fn programs(&self) -> Vec<&'static str> {
+        let mut programs = Vec::new();
+        if self.scope.is_some() {
+            programs.push("systemd-run");
+        }
+        if self.ionice.is_some() {
+            programs.push("ionice");
+        }
+        if self.nice.is_some() {
+            programs.push("nice");
+        }
+        programs
+    }
+
+    pub fn in_scope(&self) -> bool {
+        self.scope.is_some()
+    }
+
+    /// `--user` 模式下需要额外传给systemd-run/systemctl的参数
+    fn user_args(&self) -> Vec<String> {
+        if self.scope != Some(ScopeMode::User) {
+            return Vec::new();
+        }
+        let mut args = vec!["--user".to_string()];
+        if paths::is_root() {
+            args.push(format!("--machine={}@.host", self.user_name));
+        }
+        args
+    }
+
+    /// 停止scope单元的命令，提示给用户
+    pub fn stop_command(&self, unit: &str) -> String {
+        let mut args = vec!["systemctl".to_string()];
+        args.extend(self.user_args());
+        args.push(format!("stop {}.scope", unit));
+        args.join(" ")
+    }
+
+    /// 创建运行 `program` 的命令，按配置在前面加上包装命令，调用方继续追加program的参数
+    ///
+    /// 在scope单元中运行时 `unit` 为单元名
+    pub fn command(&self, program: &Path, unit: &str) -> Command {
+        let mut wrapper: Vec<OsString> = Vec::new();
+        if self.scope.is_some() {
+            wrapper.push("systemd-run".into());
+            wrapper.extend(self.user_args().into_iter().map(OsString::from));
+            wrapper.extend(["--scope", "--quiet", "--collect"].map(OsString::from));
+            wrapper.push(format!("--unit={}", unit).into());
+            if let Some(quota) = &self.cpu_quota {
+                wrapper.push("-p".into());
+                wrapper.push(format!("CPUQuota={}", quota).into());
+            }
+            wrapper.push("--".into());
+        }
+        if let Some(ionice) = &self.ionice {
+            wrapper.push("ionice".into());
+            wrapper.extend(ionice.args().into_iter().map(OsString::from));
+        }
+        if let Some(nice) = self.nice {
+            wrapper.extend(["nice".into(), "-n".into(), nice.to_string().into()]);
+        }
+        if wrapper.is_empty() {
+            return Command::new(program);
+        }
+        let mut cmd = Command::new(&wrapper[0]);
+        cmd.args(&wrapper[1..]).arg(program);
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_io_classes() {
+        assert!(matches!(IoClass::parse("idle"), Ok(IoClass::Idle)));
+        assert!(matches!(
+            IoClass::parse("best-effort"),
+            Ok(IoClass::BestEffort(None))
+        ));
+        assert!(matches!(
+            IoClass::parse("best-effort: 4"),
+            Ok(IoClass::BestEffort(Some(4)))
+        ));
+        assert!(matches!(
+            IoClass::parse("realtime:0"),
+            Ok(IoClass::Realtime(Some(0)))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_io_classes() {
+        for raw in ["idle:3", "best-effort:8", "realtime:high", "batch", ""] {
+            assert!(IoClass::parse(raw).is_err(), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn io_class_args() {
+        assert_eq!(IoClass::Idle.args(), ["-c", "3"]);
+        assert_eq!(IoClass::BestEffort(Some(7)).args(), ["-c", "2", "-n", "7"]);
+        assert_eq!(IoClass::Realtime(None).args(), ["-c", "1"]);
+    }
+}
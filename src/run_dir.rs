@@ -0,0 +1,139 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::{PermissionsExt, chown};
+use std::path::{Component, Path, PathBuf};
+
+/// 每次运行独立的工作目录，目录名为RUN_ID
+///
+/// 钩子脚本通过 RUN_DIR 环境变量拿到目录，把截图、导出的报告等产物放进来；
+/// 运行结束时本工具写入 summary.txt，旧目录按 RUN_DIR_KEEP 清理
+pub struct RunDir {
+    id: String,
+    path: PathBuf,
+}
+
+// 运行结束时写入的运行总结
+const SUMMARY_FILE: &str = "summary.txt";
+
+impl RunDir {
+    /// 在 `runs` 下创建本次运行的目录，只保留最近 `keep` 次运行的目录
+    pub fn create(
+        runs: &Path,
+        id: &str,
+        keep: usize,
+        owner: Option<(u32, u32)>,
+    ) -> io::Result<Self> {
+        let path = runs.join(id);
+        fs::create_dir_all(&path)?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o700))?;
+        if let Some((uid, gid)) = owner {
+            chown(&path, Some(uid), Some(gid))?;
+        }
+        prune(runs, keep);
+        Ok(Self {
+            id: id.to_string(),
+            path,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 写入运行总结并登记目录中的产物
+    pub fn finish(&self, summary: Option<&str>) {
+        if let Some(summary) = summary
+            && let Err(e) = fs::write(self.path.join(SUMMARY_FILE), format!("{}\n", summary))
+        {
+            log::warn!("写入运行总结失败: {}", e);
+        }
+        let artifacts = artifacts(&self.path);
+        log::info!(
+            "本次运行产物{}个，位于{}",
+            artifacts.len(),
+            self.path.display()
+        );
+    }
+}
+
+/// 按目录名（以时间开头）排序的全部运行目录，最早的在前
+fn run_dirs(runs: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(runs) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// 删除超出保留数量的最早的运行目录
+fn prune(runs: &Path, keep: usize) {
+    let dirs = run_dirs(runs);
+    for dir in &dirs[..dirs.len().saturating_sub(keep)] {
+        if let Err(e) = fs::remove_dir_all(dir) {
+            log::warn!("清理运行目录{}失败: {}", dir.display(), e);
+        }
+    }
+}
+
+/// 目录下的全部文件（相对路径与大小），包括子目录中的文件
+fn artifacts(dir: &Path) -> Vec<(PathBuf, u64)> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                pending.push(path);
+            } else if let Ok(relative) = path.strip_prefix(dir) {
+                files.push((relative.to_path_buf(), meta.len()));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// `easy_maa artifacts`：不带RUN_ID时列出保留的各次运行，带RUN_ID时列出该次运行的产物
+pub fn print_artifacts(runs: &Path, run_id: Option<&str>) -> bool {
+    let Some(run_id) = run_id else {
+        let dirs = run_dirs(runs);
+        if dirs.is_empty() {
+            println!("还没有运行记录");
+        }
+        for dir in dirs {
+            let name = dir.file_name().unwrap_or_default().to_string_lossy();
+            println!("{}\t{}个产物", name, artifacts(&dir).len());
+        }
+        return true;
+    };
+    // RUN_ID只能是runs下的一级目录名，拒绝 `..`、`.`、绝对路径与多级路径
+    let mut components = Path::new(run_id).components();
+    let single = matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    );
+    let dir = runs.join(run_id);
+    if !single || !dir.is_dir() {
+        eprintln!("没有找到运行{}的工作目录", run_id);
+        return false;
+    }
+    for (path, size) in artifacts(&dir) {
+        println!("{}\t{}", size, dir.join(path).display());
+    }
+    true
+}
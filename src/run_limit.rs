@@ -0,0 +1,37 @@
+use chrono::Local;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// 记录文件内容为一行 `日期 次数`，例如 `2025-01-01 2`，日期变化后自动从0开始计数
+const COUNTER_FILE: &str = "daily_runs";
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// 读取今天已经运行过的次数，记录不存在或不是今天的记录时返回0
+pub fn today_runs(state_dir: &Path) -> u32 {
+    let today = today();
+    fs::read_to_string(state_dir.join(COUNTER_FILE))
+        .ok()
+        .and_then(|content| {
+            let (date, count) = content.trim().split_once(' ')?;
+            if date == today {
+                count.parse().ok()
+            } else {
+                None
+            }
+        })
+        .unwrap_or(0)
+}
+
+/// 今天的运行次数加一
+pub fn record_run(state_dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(state_dir)?;
+    let count = today_runs(state_dir) + 1;
+    fs::write(
+        state_dir.join(COUNTER_FILE),
+        format!("{} {}\n", today(), count),
+    )
+}
@@ -0,0 +1,50 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::fd::AsRawFd;
+use std::path::Path;
+use std::process;
+
+// 锁文件内容为持有锁的进程PID，仅用于提示
+const LOCK_FILE: &str = "run.lock";
+
+/// 防止同时运行两个MAA任务的锁，对锁文件加 `flock` 排他锁并在整个运行期间保持打开
+///
+/// 锁由内核在进程退出时释放，异常退出或直接 `exit` 都不会留下失效的锁；锁文件本身不删除，
+/// 删除后其他进程可能锁到不同的文件
+pub struct RunLock {
+    file: File,
+}
+
+impl RunLock {
+    /// 获取运行锁，已有其他easy_maa进程在运行时返回它的PID（读不到时为0）
+    pub fn acquire(state_dir: &Path) -> io::Result<Result<Self, u32>> {
+        std::fs::create_dir_all(state_dir)?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(state_dir.join(LOCK_FILE))?;
+        // SAFETY: fd在file的生命周期内有效
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::WouldBlock {
+                return Err(err);
+            }
+            let mut content = String::new();
+            let _ = file.read_to_string(&mut content);
+            return Ok(Err(content.trim().parse().unwrap_or(0)));
+        }
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{}", process::id())?;
+        Ok(Ok(Self { file }))
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        // 清空PID，关闭文件时释放锁
+        let _ = self.file.set_len(0);
+    }
+}
@@ -0,0 +1,253 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::fmt;
+
+/// 不运行任务的日期，如 `Sat`、`2025-05-01` 或 `2025-05-01~2025-05-07`
+#[derive(Debug, Clone)]
+pub enum SkipDate {
+    /// 每周固定跳过的日子
+    Weekly(Weekday),
+    /// 具体日期区间（含首尾），来自ICS日历时带上事件名称
+    Range {
+        start: NaiveDate,
+        end: NaiveDate,
+        summary: Option<String>,
+    },
+}
+
+impl SkipDate {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        match self {
+            SkipDate::Weekly(weekday) => date.weekday() == *weekday,
+            SkipDate::Range { start, end, .. } => *start <= date && date <= *end,
+        }
+    }
+}
+
+impl fmt::Display for SkipDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipDate::Weekly(weekday) => write!(f, "每周{}", weekday),
+            SkipDate::Range {
+                start,
+                end,
+                summary,
+            } => {
+                if start == end {
+                    write!(f, "{}", start)?;
+                } else {
+                    write!(f, "{}~{}", start, end)?;
+                }
+                match summary {
+                    Some(summary) => write!(f, " {}", summary),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// 解析 SKIP_DATES 配置，多个日期用逗号分隔
+pub fn parse(raw: &str) -> Result<Vec<SkipDate>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(parse_item)
+        .collect()
+}
+
+fn parse_item(item: &str) -> Result<SkipDate, String> {
+    let invalid = || format!("无法解析排除日期: {}", item);
+    let date = |raw: &str| NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d").map_err(|_| invalid());
+    if let Some((start, end)) = item.split_once('~') {
+        let (start, end) = (date(start)?, date(end)?);
+        if start > end {
+            return Err(invalid());
+        }
+        return Ok(SkipDate::Range {
+            start,
+            end,
+            summary: None,
+        });
+    }
+    match date(item) {
+        Ok(day) => Ok(SkipDate::Range {
+            start: day,
+            end: day,
+            summary: None,
+        }),
+        Err(_) => item
+            .parse::<Weekday>()
+            .map(SkipDate::Weekly)
+            .map_err(|_| invalid()),
+    }
+}
+
+/// 从ICS日历（如节假日订阅）中读取事件的日期区间
+///
+/// 只处理VEVENT的DTSTART/DTEND/SUMMARY，全天事件的DTEND按规范不包含在内
+pub fn parse_ics(content: &str) -> Vec<SkipDate> {
+    // ICS的长行会折行，续行以空格或制表符开头
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest),
+            _ => lines.push(line.trim_end_matches('\r').to_string()),
+        }
+    }
+    let mut events = Vec::new();
+    let (mut start, mut end, mut all_day, mut summary) = (None, None, false, None);
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, params) = name.split_once(';').unwrap_or((name, ""));
+        match key {
+            "BEGIN" if value == "VEVENT" => {
+                (start, end, all_day, summary) = (None, None, false, None);
+            }
+            "DTSTART" => {
+                start = ics_date(value);
+                all_day = params.contains("VALUE=DATE") || value.len() == 8;
+            }
+            "DTEND" => end = ics_date(value),
+            "SUMMARY" => summary = Some(value.replace("\\,", ",")),
+            "END" if value == "VEVENT" => {
+                let Some(first) = start else {
+                    continue;
+                };
+                let last = match end {
+                    Some(end) if all_day && end > first => end.pred_opt().unwrap_or(first),
+                    Some(end) if end >= first => end,
+                    _ => first,
+                };
+                events.push(SkipDate::Range {
+                    start: first,
+                    end: last,
+                    summary: summary.take(),
+                });
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+/// ICS日期形如 `20250501` 或 `20250501T100000Z`，只取日期部分
+fn ics_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(raw: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(raw, "%Y-%m-%d").unwrap()
+    }
+
+    fn range(skip: &SkipDate) -> (NaiveDate, NaiveDate, Option<&str>) {
+        match skip {
+            SkipDate::Range {
+                start,
+                end,
+                summary,
+            } => (*start, *end, summary.as_deref()),
+            SkipDate::Weekly(_) => panic!("不是日期区间: {}", skip),
+        }
+    }
+
+    #[test]
+    fn parses_weekdays_dates_and_ranges() {
+        let dates = parse("Sat, 2025-05-01, 2025-10-01~2025-10-07").unwrap();
+        assert_eq!(dates.len(), 3);
+        // 2025-05-03是周六
+        assert!(dates[0].contains(date("2025-05-03")));
+        assert!(!dates[0].contains(date("2025-05-04")));
+        assert!(dates[1].contains(date("2025-05-01")));
+        assert!(!dates[1].contains(date("2025-05-02")));
+        assert!(dates[2].contains(date("2025-10-01")));
+        assert!(dates[2].contains(date("2025-10-07")));
+        assert!(!dates[2].contains(date("2025-10-08")));
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for raw in [
+            "2025-10-07~2025-10-01",
+            "2025-13-01",
+            "Someday",
+            "2025-05-01~",
+        ] {
+            assert!(parse(raw).is_err(), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn ics_all_day_end_is_exclusive() {
+        let ics = "\
+BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20251001\r
+DTEND;VALUE=DATE:20251009\r
+SUMMARY:国庆节\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART:20250501\r
+DTEND:20250502\r
+SUMMARY:劳动节\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+        let events = parse_ics(ics);
+        assert_eq!(
+            range(&events[0]),
+            (date("2025-10-01"), date("2025-10-08"), Some("国庆节"))
+        );
+        assert_eq!(
+            range(&events[1]),
+            (date("2025-05-01"), date("2025-05-01"), Some("劳动节"))
+        );
+    }
+
+    #[test]
+    fn ics_timed_event_keeps_end_date() {
+        let ics = "\
+BEGIN:VEVENT
+DTSTART;TZID=Asia/Shanghai:20250501T220000
+DTEND;TZID=Asia/Shanghai:20250502T020000
+SUMMARY:停服维护
+END:VEVENT
+";
+        assert_eq!(
+            range(&parse_ics(ics)[0]),
+            (date("2025-05-01"), date("2025-05-02"), Some("停服维护"))
+        );
+    }
+
+    #[test]
+    fn ics_unfolds_long_lines() {
+        let ics = "\
+BEGIN:VEVENT
+DTSTART;VALUE=DATE:20250501
+SUMMARY:劳动节\\, 
+ 调休
+DTEND;VALUE=DATE:2025
+\t0506
+END:VEVENT
+";
+        assert_eq!(
+            range(&parse_ics(ics)[0]),
+            (date("2025-05-01"), date("2025-05-05"), Some("劳动节, 调休"))
+        );
+    }
+
+    #[test]
+    fn ics_event_without_end_is_single_day() {
+        let ics = "BEGIN:VEVENT\nDTSTART;VALUE=DATE:20250501\nEND:VEVENT\n";
+        let events = parse_ics(ics);
+        assert_eq!(
+            range(&events[0]),
+            (date("2025-05-01"), date("2025-05-01"), None)
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::time::Duration;
+
+/// 运行前的系统负载阈值，任一项超过时延迟启动
+pub struct LoadLimits {
+    /// 1分钟平均负载上限，对应 LOAD_MAX
+    pub load: Option<f64>,
+    /// IO压力（PSI some avg60，百分比）上限，对应 IO_PRESSURE_MAX
+    pub io_pressure: Option<f64>,
+    /// 每次延迟的时间与最多延迟次数
+    pub delay: Duration,
+    pub retries: u32,
+}
+
+/// /proc/loadavg 的1分钟平均负载
+fn load_average() -> Option<f64> {
+    fs::read_to_string("/proc/loadavg")
+        .ok()?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// /proc/pressure/io 中 `some avg60=` 的值，内核未开启PSI时返回None
+fn io_pressure() -> Option<f64> {
+    let content = fs::read_to_string("/proc/pressure/io").ok()?;
+    let some = content.lines().find(|line| line.starts_with("some"))?;
+    some.split_whitespace()
+        .find_map(|field| field.strip_prefix("avg60="))?
+        .parse()
+        .ok()
+}
+
+impl LoadLimits {
+    /// 当前超过阈值的原因，未超过时返回None
+    fn exceeded(&self) -> Option<String> {
+        if let (Some(max), Some(load)) = (self.load, load_average())
+            && load > max
+        {
+            return Some(format!("系统负载{:.2}超过上限{:.2}", load, max));
+        }
+        if let (Some(max), Some(pressure)) = (self.io_pressure, io_pressure())
+            && pressure > max
+        {
+            return Some(format!("IO压力{:.2}%超过上限{:.2}%", pressure, max));
+        }
+        None
+    }
+
+    /// 负载过高时延迟等待，最多延迟 `retries` 次，之后无论负载如何都继续运行
+    pub async fn wait_until_idle(&self) {
+        let mut delayed = 0;
+        while let Some(reason) = self.exceeded() {
+            if delayed == self.retries {
+                log::warn!("{}，已达到最多延迟次数，继续运行", reason);
+                return;
+            }
+            delayed += 1;
+            log::warn!(
+                "{}，{}分钟后重新检查({}/{})",
+                reason,
+                self.delay.as_secs() / 60,
+                delayed,
+                self.retries
+            );
+            tokio::time::sleep(self.delay).await;
+        }
+        if delayed > 0 {
+            log::info!("系统负载已恢复，继续运行");
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::process::Command;
+
+/// 用户正在使用电脑时推迟运行，避免模拟器抢占GPU
+///
+/// 空闲时间取自logind的IdleHint，需要桌面环境（GNOME、KDE等）上报空闲状态
+pub struct UserIdle {
+    /// 用户至少空闲多久才运行，对应 USER_IDLE_MINUTES
+    pub min_idle: Duration,
+    /// 每次推迟的时间与最多推迟次数
+    pub delay: Duration,
+    pub retries: u32,
+}
+
+/// `loginctl show-user` 列出的会话ID
+async fn sessions(user_name: &str) -> Option<Vec<String>> {
+    let output = Command::new("loginctl")
+        .args(["show-user", user_name, "--property=Sessions", "--value"])
+        .output()
+        .await
+        .ok()?;
+    // 用户没有登录时loginctl返回失败，视为没有会话
+    if !output.status.success() {
+        return Some(Vec::new());
+    }
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .map(String::from)
+            .collect(),
+    )
+}
+
+/// 一个会话已空闲的时长，会话处于活跃状态时为0；greeter等非用户会话返回None
+async fn session_idle(session: &str, now: Duration) -> Option<Duration> {
+    let output = Command::new("loginctl")
+        .args(["show-session", session])
+        .args(["--property=Class", "--property=IdleHint"])
+        .arg("--property=IdleSinceHint")
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let property = |key: &str| {
+        stdout.lines().find_map(|line| {
+            line.strip_prefix(key)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(str::trim)
+        })
+    };
+    if property("Class") != Some("user") {
+        return None;
+    }
+    if property("IdleHint") != Some("yes") {
+        return Some(Duration::ZERO);
+    }
+    // IdleSinceHint 为进入空闲时的时间戳（微秒）
+    let since = Duration::from_micros(property("IdleSinceHint")?.parse().ok()?);
+    Some(now.saturating_sub(since))
+}
+
+impl UserIdle {
+    /// 用户最近一次使用电脑以来的时长，没有登录会话时返回None
+    async fn idle_time(user_name: &str) -> Result<Option<Duration>, String> {
+        let sessions = sessions(user_name).await.ok_or("无法执行loginctl")?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut idle: Option<Duration> = None;
+        for session in &sessions {
+            if let Some(session_idle) = session_idle(session, now).await {
+                idle = Some(idle.map_or(session_idle, |idle| idle.min(session_idle)));
+            }
+        }
+        Ok(idle)
+    }
+
+    /// 用户活跃时推迟等待，最多推迟 `retries` 次，仍未空闲时返回false，本次不再运行
+    pub async fn wait_until_idle(&self, user_name: &str) -> bool {
+        let mut delayed = 0;
+        loop {
+            let idle = match Self::idle_time(user_name).await {
+                Ok(Some(idle)) => idle,
+                Ok(None) => return true,
+                Err(e) => {
+                    log::warn!("{}，跳过空闲检测", e);
+                    return true;
+                }
+            };
+            if idle >= self.min_idle {
+                if delayed > 0 {
+                    log::info!("用户已空闲{}分钟，继续运行", idle.as_secs() / 60);
+                }
+                return true;
+            }
+            if delayed == self.retries {
+                log::warn!(
+                    "用户{}仍在使用电脑，已达到最多推迟次数，跳过本次运行",
+                    user_name
+                );
+                return false;
+            }
+            delayed += 1;
+            log::warn!(
+                "用户{}正在使用电脑（空闲{}分钟，需要{}分钟），{}分钟后重新检查({}/{})",
+                user_name,
+                idle.as_secs() / 60,
+                self.min_idle.as_secs() / 60,
+                self.delay.as_secs() / 60,
+                delayed,
+                self.retries
+            );
+            tokio::time::sleep(self.delay).await;
+        }
+    }
+}